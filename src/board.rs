@@ -1,155 +1,559 @@
 use std::hash::Hasher;
 use std::hash::Hash;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, RwLock};
 use std::{fs, mem, iter::{repeat, successors}, ops::Deref};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
 use either::Either;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use smallvec::SmallVec;
 
+use crate::error::Error;
+
+
+/// Whether `Board::show` is allowed to use ANSI colors.
+static COLORED_OUTPUT: AtomicBool = AtomicBool::new(true);
+
+/// ANSI escape code resetting all colors and styles.
+const RESET_STYLE: &str = "\x1b[0m";
+/// ANSI escape code added to the style of a highlighted vehicle.
+const HIGHLIGHT_STYLE: &str = "\x1b[4m";
+/// 256-color background of the target vehicle, usually the red "X" car.
+const X_COLOR: u8 = 160;
+/// Light 256-color backgrounds used for cars, combined with black text.
+const CAR_COLORS: [u8; 12] = [33, 40, 220, 208, 129, 44, 213, 154, 111, 180, 85, 141];
+/// Dark 256-color backgrounds used for trucks, combined with bold white text.
+const TRUCK_COLORS: [u8; 6] = [19, 22, 94, 54, 23, 58];
+
+
+/// Enable or disable colored output of `Board::show`. Colors are never used
+/// when stdout is not a terminal.
+pub fn set_colored_output(enabled: bool) {
+    COLORED_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+
+/// Returns the ANSI escape code used to draw the vehicle with the given `id`.
+/// 
+/// The color only depends on the name, see `VehicleId.color_number`, so a 
+/// vehicle keeps its color between turns and games. Vehicles with a `length` of 3 or more are trucks and get a darker
+/// color with bold text, the target vehicle `target_id` is always red.
+fn vehicle_style(id: VehicleId, length: u8, target_id: VehicleId) -> String {
+    if id == target_id {
+        format!("\x1b[1;97;48;5;{X_COLOR}m")
+    } else if length >= 3 {
+        format!("\x1b[1;97;48;5;{}m", TRUCK_COLORS[id.color_number() % TRUCK_COLORS.len()])
+    } else {
+        format!("\x1b[30;48;5;{}m", CAR_COLORS[id.color_number() % CAR_COLORS.len()])
+    }
+}
+
+
+/// Name of a vehicle, interned in a global table so it can be copied and 
+/// compared as a number. Equal names always get the same `VehicleId`.
+/// 
+/// The red "X" car always has `VehicleId::X`.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct VehicleId(u32);
+
+
+/// The interning table of `VehicleId`: the name of every id, and the id of
+/// every name.
+#[derive(Default)]
+struct VehicleNames {
+    names: Vec<String>,
+    ids: HashMap<String, VehicleId>,
+}
+
+
+/// Every vehicle name seen so far, see `VehicleId::new`. Starts with "X", so 
+/// it gets `VehicleId::X`.
+static VEHICLE_NAMES: LazyLock<RwLock<VehicleNames>> = LazyLock::new(|| {
+    let x = String::from("X");
+    RwLock::new(VehicleNames {
+        names: vec![x.clone()],
+        ids: HashMap::from([(x, VehicleId::X)]),
+    })
+});
+
+
+impl VehicleId {
+    /// Id of the red "X" car.
+    pub const X: VehicleId = VehicleId(0);
+
+
+    /// Returns the id of the vehicle called `name`, adding it to the table 
+    /// of names when it is new.
+    /// 
+    /// Returns an error if `name` is empty, or contains whitespace, control
+    /// characters or commas, which would break the gameboard files. Names
+    /// starting with "#" would be read as directives, and the names of walls 
+    /// and empty tiles in the text grid are taken as well.
+    pub fn new(name: &str) -> Result<VehicleId, Error> {
+        if name.is_empty() || name.chars().any(|c| c.is_whitespace() || c.is_control() || c == ',') {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        if name.starts_with('#') || name == GRID_WALL || name == GRID_EMPTY {
+            return Err(Error::InvalidName(name.to_string()));
+        }
+        if let Some(id) = VEHICLE_NAMES.read().expect("Vehicle names poisoned.").ids.get(name) {
+            return Ok(*id);
+        }
+
+        let mut table = VEHICLE_NAMES.write().expect("Vehicle names poisoned.");
+        // another thread may have added the name in the meantime
+        if let Some(id) = table.ids.get(name) {
+            return Ok(*id);
+        }
+        let id = VehicleId(u32::try_from(table.names.len())
+            .map_err(|_| Error::InvalidName(name.to_string()))?);
+        table.names.push(name.to_string());
+        table.ids.insert(name.to_string(), id);
+        Ok(id)
+    }
+
+
+    /// Returns the name of the vehicle.
+    pub fn name(&self) -> String {
+        VEHICLE_NAMES.read().expect("Vehicle names poisoned.").names[self.index()].clone()
+    }
+
+
+    /// Returns the number of the id, which counts up from 0 in the order the 
+    /// names were first seen.
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+
+
+    /// Returns a number picking the color of the vehicle. Unlike the `index`
+    /// it only depends on the name, so the vehicle gets the same color 
+    /// whatever boards were read before.
+    pub fn color_number(&self) -> usize {
+        self.name().bytes().fold(0, |number: usize, byte| number.wrapping_mul(31).wrapping_add(usize::from(byte)))
+    }
+}
+
+
+impl fmt::Display for VehicleId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // padding applies to the name, like it would for a `String`
+        f.pad(&self.name())
+    }
+}
+
+
+/// A `VehicleId` is (de)serialized as its name.
+impl Serialize for VehicleId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.name().serialize(serializer)
+    }
+}
+
+
+impl<'de> Deserialize<'de> for VehicleId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        VehicleId::new(&name)
+            .map_err(|_| serde::de::Error::custom(format!("{name} is an invalid vehicle id")))
+    }
+}
+
 
 /// Struct used to mark vehicle locations on the board.
 #[derive(Debug, Clone, Hash, Eq, PartialEq)]
 pub struct VehicleSegment {
-    id: u8,
+    id: VehicleId,
     direction: Direction,
     segments_left: u8,
+    one_way: Option<Side>,
 }
 
 
 impl VehicleSegment {
-    /// Creates a new `VehicleSegment`.
-    /// 
-    /// `id` must be a `String` of one or two capital letters, equal to or less than "GZ".
-    /// The vehicle moving seems to break above "DA" however so, don't use that.
-    pub fn new(id: String, direction: Direction, segments_left: u8) -> Result<VehicleSegment, ()> {
-        let veh_id = VehicleSegment::string_to_veh_id(id).expect("String to id failed.");
-        
-        Ok(VehicleSegment {
-            id: veh_id,
-            direction,
-            segments_left,
-        })
-    }
-
-    /// Stores a one or two letter alphabetical string in a single `u8`.
-    /// 
-    /// `id` must be a `String` of one or two capital letters, equal to or less than "GZ".
-    fn string_to_veh_id(id: String) -> Result<u8, ()> {
-        let id_as_bytes = id.as_bytes();
-        // the right side of the veh_id
-        let id1;
-        // the left side of the veh_id
-        let id2;
-
-        // id is a single character
-        if id_as_bytes.len() == 1 {
-            // character must be ASCII capital letter
-            if 65 <= id_as_bytes[0] && id_as_bytes[0] <= 90 {
-                id1 = id_as_bytes[0] - 64;
-                id2 = 0;
-            } else {
-                return Err(());
-            }
-        // id is two characters
-        } else if id_as_bytes.len() == 2 {
-            // characters must be ASCII capital letters
-            if     65 <= id_as_bytes[0] && id_as_bytes[0] <= 71 
-                && 65 <= id_as_bytes[1] && id_as_bytes[1] <= 90 
-            {
-                id1 = id_as_bytes[1] - 64;
-                id2 = (id_as_bytes[0] - 64) << 5;
-            } else {
-                return Err(());
-            }
-        // id can't be longer than two characters (or empty)
-        } else {
-            return Err(());
-        }
-        
-        Ok(id1 + id2)
+    /// Returns the name of the vehicle.
+    pub fn id_string(&self) -> String {
+        self.id.name()
     }
 
 
-    /// Decodes the `VehicleSegment.id` back into a string.
-    pub fn id_string(&self) -> String {
-        let letter1 = (self.id & 0b00011111) + 64;
-        let mut letter2 = (self.id & 0b11100000) >> 5;
-        
-        if letter2 == 0 {
-            letter2 = 32;
-            String::from_utf8_lossy(&[letter1, letter2]).to_string()
-        } else {
-            letter2 += 64;
-            String::from_utf8_lossy(&[letter2, letter1]).to_string()
-        }
+    /// Returns `true` if the vehicle may move over `direction` tiles, which
+    /// a one-way vehicle only may towards its side.
+    fn can_move(&self, direction: i8) -> bool {
+        self.one_way.is_none_or(|side| side.sign() == direction.signum())
     }
 }
 
 
 /// Enum to indicate the direction of a vehicle. Should be made with the
 /// `Direction::from_str` method. 
-#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+/// 
+/// `Free` pieces are not vehicles but blocks of any shape, like those of 
+/// Klotski, which slide both horizontally and vertically.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub enum Direction {
     Horizontal,
     Vertical,
+    Free,
 }
 
 
 impl Direction {
     /// Create a `Direction` enum from a stringslice.
-    fn from_str(dir_char: &str) -> Self {
+    /// 
+    /// Returns an error if `dir_char` is not "H", "V" or "F".
+    fn from_str(dir_char: &str) -> Result<Self, Error> {
         match dir_char {
-            "H" => Self::Horizontal,
-            "V" => Self::Vertical,
-            _ => panic!("{dir_char} is an invalid direction!")
+            "H" => Ok(Self::Horizontal),
+            "V" => Ok(Self::Vertical),
+            "F" => Ok(Self::Free),
+            _ => Err(Error::Parse(format!("direction {dir_char:?}"))),
+        }
+    }
+
+
+    /// Returns the stringslice used for this direction in gameboard files.
+    pub const fn to_str(&self) -> &'static str {
+        match self {
+            Self::Horizontal => "H",
+            Self::Vertical => "V",
+            Self::Free => "F",
+        }
+    }
+}
+
+
+/// Side of the board an `Exit` is on.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+
+impl Side {
+    /// Create a `Side` from its name in gameboard files, eg "right".
+    fn from_name(name: &str) -> Result<Self, Error> {
+        match name.to_lowercase().as_str() {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "top" => Ok(Self::Top),
+            "bottom" => Ok(Self::Bottom),
+            _ => Err(Error::Parse(format!("side {name:?}"))),
+        }
+    }
+
+
+    /// Returns the name of this side in gameboard files.
+    pub const fn to_str(&self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+        }
+    }
+
+
+    /// Returns the direction of the vehicles that can drive towards this side.
+    pub const fn direction(&self) -> Direction {
+        match self {
+            Self::Left | Self::Right => Direction::Horizontal,
+            Self::Top | Self::Bottom => Direction::Vertical,
+        }
+    }
+
+
+    /// Returns the sign of the `Move.direction` of moves towards this side.
+    const fn sign(&self) -> i8 {
+        match self {
+            Self::Left | Self::Top => -1,
+            Self::Right | Self::Bottom => 1,
+        }
+    }
+
+
+    /// Returns this side on a board mirrored from top to bottom.
+    const fn mirrored(&self) -> Side {
+        match self {
+            Self::Top => Self::Bottom,
+            Self::Bottom => Self::Top,
+            _ => *self,
+        }
+    }
+
+
+    /// Returns the arrow pointing towards this side, as drawn by `Board.render`.
+    const fn arrow(&self) -> char {
+        match self {
+            Self::Left => '<',
+            Self::Right => '>',
+            Self::Top => '^',
+            Self::Bottom => 'v',
+        }
+    }
+}
+
+
+/// The opening the target vehicle has to drive through to win. `position` 
+/// is the row of an exit on the left or right side, or the column of an 
+/// exit on the top or bottom side, starting at 0.
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Exit {
+    pub side: Side,
+    pub position: usize,
+}
+
+
+impl Exit {
+    /// Returns `true` if the exit is on the side of a board of `width` 
+    /// columns and `height` rows.
+    pub const fn fits(&self, width: usize, height: usize) -> bool {
+        match self.side {
+            Side::Left | Side::Right => self.position < height,
+            Side::Top | Side::Bottom => self.position < width,
         }
     }
+
+
+    /// Returns this exit on a board of `height` rows mirrored from top to
+    /// bottom. The exit has to fit on the board, see `Exit.fits`, which
+    /// `Board` makes sure of.
+    const fn mirrored(&self, height: usize) -> Exit {
+        match self.side {
+            Side::Left | Side::Right => Exit { side: self.side, position: height - 1 - self.position },
+            _ => Exit { side: self.side.mirrored(), position: self.position },
+        }
+    }
+}
+
+
+/// Position and shape of a whole vehicle, as described by a line of a 
+/// gameboard file. Get them by running the `vehicles` method on your `Board`.
+/// 
+/// A free piece is described by a horizontal run of `length` tiles per row,
+/// the piece being all runs with its `id` together.
+/// 
+/// Unlike in the gameboard files, `col` and `row` start counting at 0.
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
+pub struct VehiclePlacement {
+    pub id: VehicleId,
+    pub direction: Direction,
+    pub col: usize,
+    pub row: usize,
+    pub length: u8,
+    /// The only side a one-way vehicle may drive towards, or `None` if the
+    /// vehicle can drive both ways.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub one_way: Option<Side>,
+}
+
+
+impl VehiclePlacement {
+    /// Creates a new `VehiclePlacement` of the vehicle named `id`, with its 
+    /// top left segment at `col` and `row`.
+    /// 
+    /// `id` must be a valid vehicle name, see `VehicleId::new`.
+    pub fn new(id: &str, direction: Direction, col: usize, row: usize, length: u8) -> Result<VehiclePlacement, Error> {
+        Ok(VehiclePlacement {
+            id: VehicleId::new(id)?,
+            direction,
+            col,
+            row,
+            length,
+            one_way: None,
+        })
+    }
+
+
+    /// Returns the name of the vehicle.
+    pub fn id_string(&self) -> String {
+        self.id.name()
+    }
 }
 
 
-/// Enum used to build up the `board`. Can contain a Vehicle or be Empty.
+/// Enum used to build up the `board`. Can contain a Vehicle, a Wall or be 
+/// Empty. Walls block vehicles, but never move themselves.
 /// 
 /// Empty is set as the `#[default]`.
 #[derive(Default, Debug, Clone, Hash, Eq, PartialEq)]
 pub enum Tile {
     Vehicle(VehicleSegment),
+    Wall,
     #[default]
     Empty,
 }
-use Tile::{Empty, Vehicle};
+use Tile::{Empty, Vehicle, Wall};
+
+
+/// The direction, one-way side and tile locations of a piece, see
+/// `Board.has_same_pieces`.
+type PieceShape = (Direction, Option<Side>, Vec<(isize, isize)>);
 
 
 impl Tile {
     /// Returns `True` if tile is `Tile::Empty`
     const fn empty(&self) -> bool {
         match self {
-            Self::Vehicle(_) => false,
+            Self::Vehicle(_) | Self::Wall => false,
             Self::Empty => true,
         }
     }
 }
 
 
+/// Name of a wall tile in the text grid, see `Board::from_str`.
+const GRID_WALL: &str = "x";
+/// Name of an empty tile in the text grid, see `Board::from_str`.
+const GRID_EMPTY: &str = ".";
+
+
+/// Returns the direction of a vehicle covering the `locations`, given in 
+/// row-major order, or `None` if they are not a straight bar of at least two
+/// tiles.
+fn bar_direction(locations: &[(usize, usize)]) -> Option<Direction> {
+    let &[(row, col), (next_row, _), ..] = locations else {
+        return None;
+    };
+    let direction = if next_row == row {
+        Direction::Horizontal
+    } else {
+        Direction::Vertical
+    };
+
+    // every tile must follow the previous one in the same row or column
+    let straight = locations.iter().enumerate().all(|(offset, location)| match direction {
+        Direction::Horizontal => *location == (row, col + offset),
+        _ => *location == (row + offset, col),
+    });
+    straight.then_some(direction)
+}
+
+
+/// Returns the `number`th name of a vehicle in the order "A", "B", ..., "Z",
+/// "AA", "AB" and so on, starting at 1, see `Board.relabeled`.
+fn label_name(number: usize) -> String {
+    let mut name = vec![];
+    let mut number = number;
+    while number > 0 {
+        number -= 1;
+        name.push(char::from(b'A' + (number % 26) as u8));
+        number /= 26;
+    }
+    name.iter().rev().collect()
+}
+
+
+/// Returns `true` if every location can be reached from the first one by 
+/// stepping to horizontally or vertically neighbouring locations.
+fn is_connected(locations: &[(usize, usize)]) -> bool {
+    let Some(&first) = locations.first() else {
+        return true;
+    };
+    let mut reached = HashSet::from([first]);
+    let mut unvisited = vec![first];
+
+    while let Some((row, col)) = unvisited.pop() {
+        for neighbour in locations {
+            if row.abs_diff(neighbour.0) + col.abs_diff(neighbour.1) == 1 && reached.insert(*neighbour) {
+                unvisited.push(*neighbour);
+            }
+        }
+    }
+    reached.len() == locations.len()
+}
+
+
 /// A class representing a move on the board.
 /// Get valid Moves by running the `possible_moves` method om your `Board`.
-#[derive(Debug, Clone, Hash)]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Move {
-    pub vehicle_id: u8,
+    pub vehicle_id: VehicleId,
     pub direction: i8,
+    /// The axis a free piece slides along, or `None` for vehicles, which 
+    /// always drive along their own direction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub axis: Option<Direction>,
 }
 
 
 impl Move {
-    /// Decodes the `Move.vehicle_id` back into a string.
+    /// Creates a new `Move` of the vehicle named `id` over `direction` tiles.
+    /// 
+    /// Returns an error if `id` is not a valid vehicle name. The `Move` is
+    /// not checked against any `Board`.
+    pub fn new(id: &str, direction: i8) -> Result<Move, Error> {
+        Ok(Move {
+            vehicle_id: VehicleId::new(id)?,
+            direction,
+            axis: None,
+        })
+    }
+
+
+    /// Reads moves from the csv format written by `Board.export`. Moves of 
+    /// free pieces have their axis, "H" or "V", in a third column.
+    pub fn from_csv(contents: &str) -> Result<Vec<Move>, Error> {
+        // vehicle names never need quotes, see `VehicleId::new`
+        csv::ReaderBuilder::new()
+            .flexible(true)
+            .quoting(false)
+            .from_reader(contents.as_bytes())
+            .records()
+            .map(|record| {
+                let record = record.map_err(|error| Error::Parse(format!("move: {error}")))?;
+                let invalid = || Error::Parse(format!("move {:?}", record.iter().collect::<Vec<_>>().join(",")));
+                let mut veh_move = Move::new(
+                    record.get(0).ok_or_else(invalid)?, 
+                    record.get(1).ok_or_else(invalid)?.trim().parse().map_err(|_| invalid())?,
+                )?;
+                veh_move.axis = match record.get(2).map(str::trim) {
+                    None | Some("") => None,
+                    Some("H") => Some(Direction::Horizontal),
+                    Some("V") => Some(Direction::Vertical),
+                    Some(_) => return Err(invalid()),
+                };
+                Ok(veh_move)
+            })
+            .collect()
+    }
+
+
+    /// Returns the name of the moved vehicle.
     pub fn get_id_string(&self) -> String {
-        let letter1 = (self.vehicle_id & 0b00011111) + 64;
-        let mut letter2 = (self.vehicle_id & 0b1110000) >> 5;
+        self.vehicle_id.name()
+    }
 
-        if letter2 == 0 {
-            String::from_utf8_lossy(&[letter1]).to_string()
-        } else {
-            letter2 += 64;
-            String::from_utf8_lossy(&[letter2, letter1]).to_string()
+
+    /// Returns the distance of the move as typed in manual games: the 
+    /// number of tiles, prefixed by "h" or "v" for the axis of a free piece.
+    pub fn distance_string(&self) -> String {
+        match self.axis {
+            Some(Direction::Horizontal) => format!("h{}", self.direction),
+            Some(Direction::Vertical) => format!("v{}", self.direction),
+            _ => self.direction.to_string(),
+        }
+    }
+}
+
+
+/// Describes the move like "A by -2", or "A by 1 vertically" for free pieces.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} by {}", self.vehicle_id, self.direction)?;
+        match self.axis {
+            Some(Direction::Horizontal) => write!(f, " horizontally"),
+            Some(Direction::Vertical) => write!(f, " vertically"),
+            _ => Ok(()),
         }
     }
 }
@@ -168,24 +572,88 @@ struct LinkedHistory {
 
 /// A struct representing a game of Rush Hour. The struct contains a 2D
 /// `Vec`-like object representing the gameboard and an optional `LinkedHistory`.
-#[derive(Clone, Hash, Debug)]
+/// 
+/// A `Board` is (de)serialized as a `BoardData`.
+#[derive(Clone, Hash, Debug, Serialize, Deserialize)]
+#[serde(into = "BoardData", try_from = "BoardData")]
 pub struct Board {
     pub contents: SmallVec<[SmallVec<[Tile; 12]>; 12]>,
     previous: Arc<Option<LinkedHistory>>,
+    /// The vehicle that has to reach the exit.
+    target: VehicleId,
+    /// The exit, or `None` for the default exit, see `Board.exit`.
+    exit: Option<Exit>,
+}
+
+
+/// The serialized form of a `Board`: its size, the vehicles on it, and the 
+/// target and exit if they are not the default ones. Like the other board 
+/// formats it leaves out the moves made so far, which a `Solution` holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoardData {
+    pub width: u8,
+    pub height: u8,
+    pub vehicles: Vec<VehiclePlacement>,
+    /// The (row, col) of every wall, starting at 0.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub walls: Vec<(usize, usize)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<VehicleId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit: Option<Exit>,
+}
+
+
+impl From<Board> for BoardData {
+    fn from(board: Board) -> Self {
+        BoardData {
+            width: u8::try_from(board.width()).expect("Board is too large."),
+            height: u8::try_from(board.height()).expect("Board is too large."),
+            vehicles: board.vehicles(),
+            walls: board.walls(),
+            target: (board.target != VehicleId::X).then_some(board.target),
+            exit: board.exit,
+        }
+    }
+}
+
+
+impl TryFrom<BoardData> for Board {
+    type Error = Error;
+
+    fn try_from(data: BoardData) -> Result<Self, Self::Error> {
+        let mut board = Board::with_size(data.width, data.height);
+        board.target = data.target.unwrap_or(VehicleId::X);
+        board.set_exit(data.exit)?;
+        for wall in &data.walls {
+            board.add_wall(wall)?;
+        }
+        for vehicle in &data.vehicles {
+            board.place(vehicle)?;
+        }
+        Ok(board)
+    }
 }
 
 
 impl Board {
     /// Create a new, empty `Board` of size `size x size` and a an empty history.
     pub fn new(size: u8) -> Self {
+        Self::with_size(size, size)
+    }
+
+
+    /// Create a new, empty `Board` of `width` columns and `height` rows, 
+    /// with an empty history.
+    pub fn with_size(width: u8, height: u8) -> Self {
         // create the rows
-        let mut board_vecs = SmallVec::<[_; 12]>::with_capacity(size.into());
+        let mut board_vecs = SmallVec::<[_; 12]>::with_capacity(height.into());
 
         // create the columns
-        for _ in 0..size {
-            let mut new_col = SmallVec::<[_; 12]>::with_capacity(size.into());
+        for _ in 0..height {
+            let mut new_col = SmallVec::<[_; 12]>::with_capacity(width.into());
             // fill the columns with empty tiles
-            for _ in 0..size {
+            for _ in 0..width {
                 new_col.push(Tile::Empty);
             }
             board_vecs.push(new_col);
@@ -194,66 +662,492 @@ impl Board {
         Self {
             contents: board_vecs,
             previous: Arc::new(Option::None),
+            target: VehicleId::X,
+            exit: None,
+        }
+    }
+
+
+    /// Returns the number of columns of the board.
+    pub fn width(&self) -> usize {
+        self.contents.first().map_or(0, |row| row.len())
+    }
+
+
+    /// Returns the number of rows of the board.
+    pub fn height(&self) -> usize {
+        self.contents.len()
+    }
+
+
+    /// Returns the vehicle that has to reach the exit, the red "X" car 
+    /// unless set otherwise.
+    pub fn target(&self) -> VehicleId {
+        self.target
+    }
+
+
+    /// Make `target` the vehicle that has to reach the exit.
+    pub fn set_target(&mut self, target: VehicleId) {
+        self.target = target;
+    }
+
+
+    /// Returns the exit of the board. Unless set otherwise, a horizontal 
+    /// target leaves on the right side of its row and a vertical target at 
+    /// the bottom of its column.
+    /// 
+    /// Returns an error if the default exit is needed but the target vehicle
+    /// is not on the board or is a free piece, which has no default exit.
+    pub fn exit(&self) -> Result<Exit, Error> {
+        if let Some(exit) = self.exit {
+            return Ok(exit);
+        }
+        let location = self.find_vehicle(self.target)?;
+        let Vehicle(vehicle) = self.get(&location) else {
+            unreachable!("Board.find_vehicle returned an empty tile.");
+        };
+        Self::default_exit(location, &vehicle.direction).ok_or(Error::InvalidGoal)
+    }
+
+
+    /// Set the exit of the board, or use the default exit for `None`.
+    /// 
+    /// Returns an error if the exit is not on the side of the board.
+    pub fn set_exit(&mut self, exit: Option<Exit>) -> Result<(), Error> {
+        if let Some(exit) = exit.filter(|exit| !exit.fits(self.width(), self.height())) {
+            return Err(Error::DoesNotFit(format!("exit {} {}", exit.side.to_str(), exit.position + 1)));
+        }
+        self.exit = exit;
+        Ok(())
+    }
+
+
+    /// Returns the default exit of a target at `location` going in `direction`,
+    /// or `None` for a free piece, which does not stay in its row or column.
+    const fn default_exit(location: (usize, usize), direction: &Direction) -> Option<Exit> {
+        match direction {
+            Direction::Horizontal => Some(Exit { side: Side::Right, position: location.0 }),
+            Direction::Vertical => Some(Exit { side: Side::Bottom, position: location.1 }),
+            Direction::Free => None,
+        }
+    }
+
+
+    /// Returns the `#wall` directive of every wall, as the fields of a line of
+    /// a gameboard file. Like vehicles, walls are given by column and row 
+    /// counting from 1.
+    fn wall_directives(&self) -> Vec<Vec<String>> {
+        self.walls().into_iter()
+            .map(|(row, col)| vec![String::from("#wall"), (col + 1).to_string(), (row + 1).to_string()])
+            .collect()
+    }
+
+
+    /// Returns the `#oneway` directive of every one-way vehicle, as the 
+    /// fields of a line of a text grid. Gameboard files give the side in 
+    /// the last column of the vehicle instead.
+    fn one_way_directives(&self) -> Vec<Vec<String>> {
+        self.vehicles().into_iter()
+            .filter_map(|vehicle| Some(vec![
+                String::from("#oneway"), 
+                vehicle.id_string(), 
+                vehicle.one_way?.to_str().to_string(),
+            ]))
+            .collect()
+    }
+
+
+    /// Turns the vehicle `id` into a free piece, sliding along both axes.
+    /// 
+    /// Returns an error, leaving the board untouched, if the vehicle is not 
+    /// on the board or is one-way.
+    pub fn set_free(&mut self, id: VehicleId) -> Result<(), Error> {
+        let locations = self.piece_locations(id);
+        if locations.is_empty() {
+            return Err(Error::NoVehicle(id.name()));
+        }
+        let one_way = locations.iter().any(|location| matches!(
+            self.get(location), Vehicle(vehicle) if vehicle.one_way.is_some()
+        ));
+        if one_way {
+            return Err(Error::Parse(format!("free one-way vehicle {id}")));
+        }
+
+        for location in locations {
+            if let Vehicle(vehicle) = &mut self.contents[location.0][location.1] {
+                vehicle.direction = Direction::Free;
+                vehicle.segments_left = 0;
+            }
+        }
+        Ok(())
+    }
+
+
+    /// Returns the `#free` directive of every free piece shaped like a bar, 
+    /// which would be read as a vehicle from a text grid otherwise.
+    fn free_directives(&self) -> Vec<Vec<String>> {
+        let mut ids: Vec<VehicleId> = vec![];
+        for vehicle in self.vehicles() {
+            if vehicle.direction == Direction::Free && !ids.contains(&vehicle.id) {
+                ids.push(vehicle.id);
+            }
+        }
+
+        ids.into_iter()
+            .filter(|id| bar_direction(&self.piece_locations(*id)).is_some())
+            .map(|id| vec![String::from("#free"), id.name()])
+            .collect()
+    }
+
+
+    /// Only allows the vehicle `id` to drive towards `side`, or towards both
+    /// sides for `None`.
+    /// 
+    /// Returns an error, leaving the board untouched, if the vehicle is not 
+    /// on the board or can't drive towards `side`.
+    pub fn set_one_way(&mut self, id: VehicleId, side: Option<Side>) -> Result<(), Error> {
+        let (row, col) = self.find_vehicle(id)?;
+        let Vehicle(vehicle) = self.get(&(row, col)) else {
+            unreachable!("Board.find_vehicle returned an empty tile.");
+        };
+        if let Some(side) = side.filter(|side| side.direction() != vehicle.direction) {
+            return Err(Error::Parse(format!("one-way vehicle {id} driving {}", side.to_str())));
+        }
+
+        for tile in self.contents.iter_mut().flatten() {
+            if let Vehicle(vehicle) = tile {
+                if vehicle.id == id {
+                    vehicle.one_way = side;
+                }
+            }
+        }
+        Ok(())
+    }
+
+
+    /// Puts a wall on the empty tile at `location`.
+    /// 
+    /// Returns an error, leaving the board untouched, if the location is 
+    /// outside the board or not empty.
+    pub fn add_wall(&mut self, location: &(usize, usize)) -> Result<(), Error> {
+        let does_not_fit = || Error::DoesNotFit(format!("wall at {location:?}"));
+        let tile = self.contents.get_mut(location.0)
+            .and_then(|row| row.get_mut(location.1))
+            .ok_or_else(does_not_fit)?;
+        if !tile.empty() {
+            return Err(does_not_fit());
+        }
+        *tile = Wall;
+        Ok(())
+    }
+
+
+    /// Returns the location of every wall on the board, in row-major order.
+    pub fn walls(&self) -> Vec<(usize, usize)> {
+        self.contents.iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| tiles.iter()
+                .enumerate()
+                .filter(|(_, tile)| matches!(tile, Wall))
+                .map(move |(col, _)| (row, col))
+            )
+            .collect()
+    }
+
+
+    /// Returns `true` if `other` has the same size, walls and pieces as this
+    /// board, so it can only differ in where the pieces are. Boards with
+    /// other pieces can never be reached from each other by moving.
+    pub fn has_same_pieces(&self, other: &Board) -> bool {
+        self.width() == other.width()
+            && self.height() == other.height()
+            && self.walls() == other.walls()
+            && self.piece_shapes() == other.piece_shapes()
+    }
+
+
+    /// Returns the direction, one-way side and shape of every piece, the
+    /// shape as the locations of its tiles relative to its first tile in
+    /// row-major order.
+    fn piece_shapes(&self) -> HashMap<VehicleId, PieceShape> {
+        let mut shapes: HashMap<VehicleId, PieceShape> = HashMap::new();
+        let mut first_tiles = HashMap::new();
+
+        for (row, tiles) in self.contents.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Vehicle(vehicle) = tile {
+                    let (first_row, first_col) = *first_tiles.entry(vehicle.id).or_insert((row, col));
+                    shapes.entry(vehicle.id)
+                        .or_insert_with(|| (vehicle.direction.clone(), vehicle.one_way, vec![]))
+                        .2.push((
+                            row as isize - first_row as isize,
+                            col as isize - first_col as isize,
+                        ));
+                }
+            }
+        }
+        shapes
+    }
+
+
+    /// Returns `true` if the board uses the red "X" car and its default exit,
+    /// like the classic puzzles.
+    pub fn has_default_goal(&self) -> bool {
+        self.target == VehicleId::X && self.exit.is_none()
+    }
+
+
+    /// Returns the `#target` and `#exit` directives describing a goal that 
+    /// is not the default one, as the fields of a line of a gameboard file.
+    /// Positions start counting at 1, like the vehicles in gameboard files.
+    fn goal_directives(&self) -> Vec<Vec<String>> {
+        let mut directives = vec![];
+        if self.target != VehicleId::X {
+            directives.push(vec![String::from("#target"), self.target.name()]);
+        }
+        if let Some(exit) = self.exit {
+            directives.push(vec![
+                String::from("#exit"), 
+                exit.side.to_str().to_string(), 
+                (exit.position + 1).to_string(),
+            ]);
+        }
+        directives
+    }
+
+
+    /// Applies a directive line of a gameboard file, split into `fields`:
+    /// "#target NAME" or "#exit SIDE POSITION", see `goal_directives`, 
+    /// "#wall COL ROW", see `wall_directives`, "#oneway NAME SIDE", see
+    /// `one_way_directives`, or "#free NAME", see `free_directives`.
+    /// 
+    /// Returns an error for unknown or malformed directives.
+    fn apply_directive(&mut self, fields: &[&str]) -> Result<(), Error> {
+        let invalid = || Error::Parse(format!("directive {:?}", fields.join(" ")));
+        match fields {
+            ["#target", name] => self.target = VehicleId::new(name)?,
+            ["#free", name] => self.set_free(VehicleId::new(name)?)?,
+            ["#oneway", name, side] => self.set_one_way(VehicleId::new(name)?, Some(Side::from_name(side)?))?,
+            ["#wall", col, row] => {
+                let col = col.parse::<usize>().map_err(|_| invalid())?;
+                let row = row.parse::<usize>().map_err(|_| invalid())?;
+                self.add_wall(&(row.checked_sub(1).ok_or_else(invalid)?, col.checked_sub(1).ok_or_else(invalid)?))?;
+            }
+            ["#exit", side, position] => {
+                let position = position.parse::<usize>().map_err(|_| invalid())?;
+                self.set_exit(Some(Exit { 
+                    side: Side::from_name(side)?, 
+                    position: position.checked_sub(1).ok_or_else(invalid)?,
+                }))?;
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    /// Builds a `Board` of `width x height` from the name of the vehicle on 
+    /// every tile in row-major order, or `None` for empty tiles. The direction
+    /// and length of every vehicle follow from the tiles it covers. Pieces 
+    /// that are not a straight bar of at least two tiles become free pieces.
+    /// 
+    /// Returns an error if the number of tiles does not match the size, or a
+    /// vehicle has an invalid name or is split into unconnected parts.
+    pub fn from_tiles(width: u8, height: u8, tiles: Vec<Option<String>>) -> Result<Board, Error> {
+        let width_usize = usize::from(width);
+        if tiles.len() != width_usize * usize::from(height) {
+            return Err(Error::Parse(format!("{} tiles of a {width}x{height} board", tiles.len())));
+        }
+
+        // (row, col) of every tile of every vehicle, in row-major order
+        let mut vehicle_tiles: BTreeMap<String, Vec<(usize, usize)>> = BTreeMap::new();
+        for (index, name) in tiles.into_iter().enumerate() {
+            if let Some(name) = name {
+                vehicle_tiles.entry(name)
+                    .or_default()
+                    .push((index / width_usize, index % width_usize));
+            }
+        }
+
+        let mut board = Board::with_size(width, height);
+        for (name, locations) in vehicle_tiles {
+            if let Some(direction) = bar_direction(&locations) {
+                let (row, col) = locations[0];
+                let length = u8::try_from(locations.len())
+                    .map_err(|_| Error::DoesNotFit(format!("vehicle {name}")))?;
+                board.place(&VehiclePlacement::new(&name, direction, col, row, length)?)?;
+                continue;
+            }
+
+            if !is_connected(&locations) {
+                return Err(Error::Parse(format!("piece {name} in unconnected parts")));
+            }
+
+            // a free piece is placed as a horizontal run of tiles at a time
+            let mut runs: Vec<((usize, usize), u8)> = vec![];
+            for (row, col) in locations {
+                match runs.last_mut() {
+                    Some(((run_row, run_col), length)) if *run_row == row && *run_col + usize::from(*length) == col => {
+                        *length += 1;
+                    }
+                    _ => runs.push(((row, col), 1)),
+                }
+            }
+            for ((row, col), length) in runs {
+                board.place(&VehiclePlacement::new(&name, Direction::Free, col, row, length)?)?;
+            }
         }
+        Ok(board)
     }
 
+
     /// Prints the current boardstate to the terminal.
+    /// 
+    /// Vehicles are colored, unless colors are disabled with
+    /// `set_colored_output` or stdout is not a terminal.
     pub fn show(&self) {
-        // variable used to mark the row containing the red "X" car
-        let mut x_row = false;
+        let colored = COLORED_OUTPUT.load(Ordering::Relaxed) && io::stdout().is_terminal();
+        print!("{}", self.render(colored, None));
+    }
+
+
+    /// Prints the current boardstate to the terminal, like `Board.show`, with
+    /// the vehicle `vehicle_id` highlighted.
+    pub fn show_highlighted(&self, vehicle_id: VehicleId) {
+        let colored = COLORED_OUTPUT.load(Ordering::Relaxed) && io::stdout().is_terminal();
+        print!("{}", self.render(colored, Some(vehicle_id)));
+    }
+
+
+    /// Draws the current boardstate in box-drawing characters and returns
+    /// it as a `String`. When `colored` is `true`, every vehicle gets an 
+    /// ANSI background color, see `vehicle_style`.
+    /// 
+    /// The segments of the `highlight` vehicle are marked with a "*", and 
+    /// underlined when `colored`. The segments of one-way vehicles are 
+    /// marked with an arrow towards their side.
+    pub fn render(&self, colored: bool, highlight: Option<VehicleId>) -> String {
+        let mut output = String::new();
+        let lengths = self.vehicle_lengths();
+        // every tile is as wide as the longest name, plus the marker
+        let name_width = lengths.keys()
+            .map(|id| id.name().chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(2);
+        let border = "─".repeat(name_width + 1);
+        let exit = self.exit().ok();
+        // the border is opened with an arrow at an exit on the top or bottom
+        let border_line = |side: Side, arrow: &str| -> String {
+            (0..self.width())
+                .map(|col| match exit {
+                    Some(exit) if exit.side == side && exit.position == col => format!("{arrow:^0$}", name_width + 1),
+                    _ => border.clone(),
+                })
+                .collect()
+        };
 
         // print the top
-        print!("┌");
-        for _ in &self.contents {
-            print!("───");
-        }
-        println!("┐");
+        output.push('┌');
+        output.push_str(&border_line(Side::Top, "^"));
+        output.push_str("┐\n");
 
         // print the middle
-        for row in &self.contents {
-            print!("│");
+        for (row_num, row) in self.contents.iter().enumerate() {
+            let exit_row = |side: Side| exit.is_some_and(|exit| exit.side == side && exit.position == row_num);
+            output.push(if exit_row(Side::Left) {'<'} else {'│'});
 
             for tile in row {
-                print!(" ");
-                // check if this line contains the red "X" car
                 match tile {
                     Vehicle(vehicle) => {
-                        print!("{:2}", vehicle.id_string());
-                        if vehicle.id == VehicleSegment::string_to_veh_id("X".to_string()).expect("X conversion failed.") {x_row = true}
+                        let highlighted = highlight == Some(vehicle.id);
+                        let marker = match vehicle.one_way {
+                            _ if highlighted => '*',
+                            Some(side) => side.arrow(),
+                            None => ' ',
+                        };
+
+                        if colored {
+                            let mut style = vehicle_style(vehicle.id, lengths[&vehicle.id], self.target);
+                            if highlighted {
+                                style.push_str(HIGHLIGHT_STYLE);
+                            }
+                            let _ = write!(output, "{style}{marker}{:name_width$}{RESET_STYLE}", vehicle.id);
+                        } else {
+                            let _ = write!(output, "{marker}{:name_width$}", vehicle.id);
+                        }
                     }, 
-                    Empty => print!("  "), 
+                    Wall => output.push_str(&"█".repeat(name_width + 1)),
+                    Empty => output.push_str(&" ".repeat(name_width + 1)), 
                 }
             }
 
-            // print an arrow on the line with the red "X" car
-            println!("{}", if x_row { x_row = false; " =>"} 
-                           else {"│"}
-            );
+            // print an arrow on the line with an exit on the right
+            output.push_str(if exit_row(Side::Right) {" =>\n"} else {"│\n"});
         }
 
         // print the bottom
-        print!("└");
-        for _ in &self.contents {
-            print!("───");
+        output.push('└');
+        output.push_str(&border_line(Side::Bottom, "v"));
+        output.push_str("┘\n");
+
+        output
+    }
+
+
+    /// Returns the length of every vehicle on the board, indexed by vehicle id.
+    fn vehicle_lengths(&self) -> HashMap<VehicleId, u8> {
+        let mut lengths = HashMap::new();
+
+        for tile in self.contents.iter().flatten() {
+            if let Vehicle(vehicle) = tile {
+                // the first segment of a vehicle has the most segments left
+                let length = lengths.entry(vehicle.id).or_insert(0);
+                *length = (*length).max(vehicle.segments_left + 1);
+            }
         }
-        println!("┘");
+        lengths
     }
 
 
-    /// Returns a `Result` containing `true` when the red "X" car is at its 
-    /// rightmost position, else returns a `Result` containing `false`.
+    /// Returns a `Result` containing `true` when the target vehicle, usually
+    /// the red "X" car, is in front of the exit, else returns a `Result` 
+    /// containing `false`.
     /// 
-    /// An `Error` can be returned when the given board is invalid (eg no car named "X").
-    pub fn is_won(&self) -> Result<bool, ()> {
-        let x_location = self.find_vehicle(
-            VehicleSegment::string_to_veh_id("X".to_string())?
-        )?;
-
-        if self.contents.len().checked_sub(x_location.1).ok_or(())? == 2 {
-            return Ok(true);
+    /// An `Error` can be returned when the given board is invalid (eg no 
+    /// target vehicle, a target that can't drive towards the exit or a free
+    /// target without an exit).
+    /// 
+    /// A free target piece is in front of the exit when it touches the side 
+    /// of the exit with its first tile along that side at the exit position.
+    pub fn is_won(&self) -> Result<bool, Error> {
+        let (row, col) = self.find_vehicle(self.target)?;
+        let Vehicle(vehicle) = self.get(&(row, col)) else {
+            unreachable!("Board.find_vehicle returned an empty tile.");
+        };
+        let length = usize::from(vehicle.segments_left) + 1;
+        let exit = self.exit.or(Self::default_exit((row, col), &vehicle.direction)).ok_or(Error::InvalidGoal)?;
+
+        if vehicle.direction == Direction::Free {
+            let locations = self.piece_locations(self.target);
+            // the first tile of the piece along the side of the exit
+            let first = match exit.side {
+                Side::Right => locations.iter().find(|location| location.1 + 1 == self.width()).map(|location| location.0),
+                Side::Left => locations.iter().find(|location| location.1 == 0).map(|location| location.0),
+                Side::Bottom => locations.iter().find(|location| location.0 + 1 == self.height()).map(|location| location.1),
+                Side::Top => locations.iter().find(|location| location.0 == 0).map(|location| location.1),
+            };
+            return Ok(first == Some(exit.position));
         }
 
-        Ok(false)
+        Ok(match (exit.side, &vehicle.direction) {
+            (Side::Right, Direction::Horizontal) => row == exit.position && col + length == self.width(),
+            (Side::Left, Direction::Horizontal) => row == exit.position && col == 0,
+            (Side::Bottom, Direction::Vertical) => col == exit.position && row + length == self.height(),
+            (Side::Top, Direction::Vertical) => col == exit.position && row == 0,
+            // the target can't drive out of an exit next to it
+            _ => return Err(Error::InvalidGoal),
+        })
     }
 
 
@@ -310,17 +1204,37 @@ impl Board {
     /// 
     /// `Board` size must be the same as the board the file describes.
     /// And the file must exist.
-    pub fn fill(&mut self, file_path: &str) {
-        // println!("{}", file_path);
+    /// 
+    /// Returns an error if the file is not a valid gameboard, see 
+    /// `Board.fill_from_str`.
+    pub fn fill(&mut self, file_path: &str) -> Result<(), Error> {
         let contents = fs::read_to_string(file_path)
-            .expect("Reading file failed.");
-        
+            .map_err(|error| Error::Io(format!("{file_path}: {error}")))?;
+
+        self.fill_from_str(&contents)
+    }
+
+
+    /// Fill the given `Board` from the contents of a gameboard file.
+    /// 
+    /// `Board` size must be the same as the board the contents describe.
+    /// Lines starting with "#" are directives, see `Board.goal_directives`.
+    /// 
+    /// Returns an error for the first line that is not a valid vehicle or
+    /// directive. The lines before it have been added to the board.
+    pub fn fill_from_str(&mut self, contents: &str) -> Result<(), Error> {
         for line in contents.split('\n').skip(1) {
             // gameboard files end with a newline sometimes
             if line.is_empty() {break}
 
-            self.add_vehicle(line);
+            if line.starts_with('#') {
+                let fields = line.trim().split(',').collect::<Vec<_>>();
+                self.apply_directive(&fields)?;
+            } else {
+                self.add_vehicle(line)?;
+            }
         }
+        Ok(())
     }
 
 
@@ -328,6 +1242,12 @@ impl Board {
     /// 
     /// `veh_move` must be a valid move, eg one obtained by `Board.possible_moves`.
     pub fn move_vehicle(&mut self, veh_move: Move) {
+        if let Some(axis) = &veh_move.axis {
+            self.move_free_piece(veh_move.vehicle_id, axis, veh_move.direction);
+            self.push_history(veh_move);
+            return;
+        }
+
         let vehicle_location = self.find_vehicle(veh_move.vehicle_id)
             .expect("Vehicle not found.");
 
@@ -343,6 +1263,7 @@ impl Board {
                 panic!("Board.find_vehicle returned an invalid tile (this shouldn't happen).")
             };
 
+        // Free pieces were moved above, this is a horizontal or vertical vehicle.
         // Order of swaps should depend on the direction of the move.
         let order = if veh_move.direction < 0 {
             Either::Left(0usize..=segments.into())
@@ -358,7 +1279,7 @@ impl Board {
                     origin.1.checked_add(offset)
                         .expect("checked_add_signed failed."),
                 ),
-                Direction::Vertical => (
+                _ => (
                     origin.0.checked_add(offset)
                         .expect("checked_add_signed failed."),
                     origin.1,
@@ -371,7 +1292,7 @@ impl Board {
                     old_loc.1.checked_add_signed(veh_move.direction.into())
                         .expect("checked_add_signed failed."),
                 ),
-                Direction::Vertical => (
+                _ => (
                     old_loc.0.checked_add_signed(veh_move.direction.into())
                         .expect("checked_add_signed failed."),
                     old_loc.1,
@@ -381,10 +1302,38 @@ impl Board {
             // perform the switch
             match direction {
                 Direction::Horizontal => self.swap_horizontal(&old_loc, &new_loc),
-                Direction::Vertical => self.swap_vertical(&old_loc, &new_loc),
+                _ => self.swap_vertical(&old_loc, &new_loc),
+            };
+        }
+
+        self.push_history(veh_move);
+    }
+
+
+    /// Slides every tile of the free piece `id` over `direction` tiles along
+    /// `axis`, without adding the move to the history.
+    /// 
+    /// The tiles the piece lands on must be empty or part of the piece.
+    fn move_free_piece(&mut self, id: VehicleId, axis: &Direction, direction: i8) {
+        let locations = self.piece_locations(id);
+        let tiles = locations.iter()
+            .map(|location| self.take(location))
+            .collect::<Vec<_>>();
+
+        for (location, tile) in locations.into_iter().zip(tiles) {
+            let new_location = match axis {
+                Direction::Horizontal => (location.0, location.1.checked_add_signed(direction.into())
+                    .expect("checked_add_signed failed.")),
+                _ => (location.0.checked_add_signed(direction.into())
+                    .expect("checked_add_signed failed."), location.1),
             };
+            self.contents[new_location.0][new_location.1] = tile;
         }
+    }
+
 
+    /// Adds `veh_move` to the history, without moving any vehicles.
+    fn push_history(&mut self, veh_move: Move) {
         let second_last = mem::take(&mut self.previous);
         let _ = mem::replace(&mut self.previous, Arc::new(Some(LinkedHistory{
                     last_move: veh_move,
@@ -393,13 +1342,27 @@ impl Board {
     }
 
 
-    /// Returns all possible moves for this board.
+    /// Executes the given `Move` on the board, after checking that it is one
+    /// of the `Board.possible_moves`.
+    /// 
+    /// Returns an error, leaving the board untouched, if the move is invalid.
+    pub fn try_move_vehicle(&mut self, veh_move: Move) -> Result<(), Error> {
+        if !self.possible_moves().unwrap_or_default().contains(&veh_move) {
+            return Err(Error::InvalidMove(veh_move));
+        }
+        self.move_vehicle(veh_move);
+        Ok(())
+    }
+
+
+    /// Returns all possible moves for this board. One-way vehicles only move
+    /// towards their side, see `VehiclePlacement.one_way`.
     /// 
     /// Iterates over the board. Once an empty tile is found, search in all 
     /// four directions for a vehicle which can move to that tile.
     /// 
     /// Returns an error if the `Board` contains no possible moves.
-    pub fn possible_moves(&self) -> Result<Vec<Move>, ()> {
+    pub fn possible_moves(&self) -> Result<Vec<Move>, Error> {
         let mut moves_vec: Vec<Move> = vec![];
 
         // iterates over locations of empty tiles
@@ -425,11 +1388,12 @@ impl Board {
 
             if let Some((Vehicle(vehicle), direction)) = candidate {
                 match vehicle.direction {
-                    Direction::Horizontal => moves_vec.push(Move { 
+                    Direction::Horizontal if vehicle.can_move(direction) => moves_vec.push(Move { 
                         vehicle_id: vehicle.id, 
                         direction,
+                        axis: None,
                     }),
-                    Direction::Vertical => (),
+                    _ => (),
                 }
             }
             
@@ -444,11 +1408,12 @@ impl Board {
 
             if let Some((Vehicle(vehicle), direction)) = candidate {
                 match vehicle.direction {
-                    Direction::Horizontal => moves_vec.push(Move { 
+                    Direction::Horizontal if vehicle.can_move(direction) => moves_vec.push(Move { 
                         vehicle_id: vehicle.id, 
                         direction,
+                        axis: None,
                     }),
-                    Direction::Vertical => (),
+                    _ => (),
                 }
             }
 
@@ -462,11 +1427,12 @@ impl Board {
 
             if let Some((Vehicle(vehicle), direction)) = candidate {
                 match vehicle.direction {
-                    Direction::Vertical => moves_vec.push(Move { 
+                    Direction::Vertical if vehicle.can_move(direction) => moves_vec.push(Move { 
                         vehicle_id: vehicle.id, 
                         direction,
+                        axis: None,
                     }),
-                    Direction::Horizontal => (),
+                    _ => (),
                 }
             }
 
@@ -480,16 +1446,19 @@ impl Board {
             
             if let Some((Vehicle(vehicle), direction)) = candidate {
                 match vehicle.direction {
-                    Direction::Vertical => moves_vec.push(Move { 
+                    Direction::Vertical if vehicle.can_move(direction) => moves_vec.push(Move { 
                         vehicle_id: vehicle.id, 
                         direction,
+                        axis: None,
                     }),
-                    Direction::Horizontal => (),
+                    _ => (),
                 }
             }
         }
+        self.free_piece_moves(&mut moves_vec);
+
         if moves_vec.is_empty() {
-            Err(())
+            Err(Error::NoMoves)
         } else {
             Ok(moves_vec)
         }
@@ -497,36 +1466,94 @@ impl Board {
 
 
     /// Adds a vehicle to the board from a csv line.
-    fn add_vehicle(&mut self, id_line: &str) {
-        let mut vehicle_info = id_line.split(',');
-        let veh_id  = vehicle_info.next().expect("veh_id parsing failed.");
-        let veh_dir = vehicle_info.next().expect("veh_dir parsing failed.");
+    /// 
+    /// Returns an error if the line is not a valid vehicle, or the vehicle 
+    /// does not fit on the board.
+    fn add_vehicle(&mut self, id_line: &str) -> Result<(), Error> {
+        let invalid = || Error::Parse(format!("vehicle {:?}", id_line.trim()));
+        let mut vehicle_info = id_line.trim().split(',');
+        let veh_id  = vehicle_info.next().ok_or_else(invalid)?;
+        let veh_dir = Direction::from_str(vehicle_info.next().ok_or_else(invalid)?)?;
 
         // some variables must be converted.
-        let mut veh_col: usize = vehicle_info.next()
-            .expect("veh_col parsing failed.")
-            .parse()
-            .unwrap();
-        let mut veh_row: usize = vehicle_info.next()
-            .expect("veh_row parsing failed.")
-            .parse()
-            .unwrap();
-        let mut veh_len: u8 = vehicle_info.next()
-            .expect("veh_len parsing failed.")
-            .parse()
-            .unwrap();
-
-        while veh_len > 0 {
-            veh_len -= 1;
-
-            self.contents[veh_row - 1][veh_col - 1] = Tile::Vehicle(VehicleSegment::new(veh_id.into(), Direction::from_str(veh_dir), veh_len).unwrap());
-
-            if veh_dir == "H" {
-                veh_col += 1;
-            } else {
-                veh_row += 1;
-            }
+        let veh_col: usize = vehicle_info.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let veh_row: usize = vehicle_info.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        let veh_len: u8 = vehicle_info.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+        // the side of a one-way vehicle is an optional last column
+        let veh_one_way = match vehicle_info.next().map(str::trim) {
+            None | Some("") => None,
+            Some(side) => Some(Side::from_name(side)?),
+        };
+
+        // gameboard files start counting at 1
+        let mut vehicle = VehiclePlacement::new(
+            veh_id, 
+            veh_dir, 
+            veh_col.checked_sub(1).ok_or_else(invalid)?, 
+            veh_row.checked_sub(1).ok_or_else(invalid)?, 
+            veh_len,
+        )?;
+        vehicle.one_way = veh_one_way;
+        self.place(&vehicle)
+    }
+
+
+    /// Returns `true` if the `vehicle` is on the board at its position. For 
+    /// a free piece only the tiles of this run are checked.
+    pub fn has_vehicle(&self, vehicle: &VehiclePlacement) -> bool {
+        if vehicle.direction == Direction::Free {
+            return (0..usize::from(vehicle.length)).all(|offset| matches!(
+                self.contents.get(vehicle.row).and_then(|row| row.get(vehicle.col + offset)),
+                Some(Vehicle(segment)) if segment.id == vehicle.id && segment.direction == Direction::Free
+            ));
+        }
+
+        // only the first segment has all other segments left behind it
+        matches!(
+            self.contents.get(vehicle.row).and_then(|row| row.get(vehicle.col)),
+            Some(Vehicle(segment)) if segment.id == vehicle.id 
+                && segment.direction == vehicle.direction 
+                && segment.segments_left + 1 == vehicle.length
+        )
+    }
+
+
+    /// Puts the `vehicle` on the board.
+    /// 
+    /// Returns an error, leaving the board untouched, if the vehicle does not
+    /// fit on the board, overlaps another vehicle or is one-way towards a 
+    /// side it can't drive to.
+    pub fn place(&mut self, vehicle: &VehiclePlacement) -> Result<(), Error> {
+        if let Some(side) = vehicle.one_way.filter(|side| side.direction() != vehicle.direction) {
+            return Err(Error::Parse(format!("one-way vehicle {} driving {}", vehicle.id, side.to_str())));
+        }
+
+        let locations = (0..usize::from(vehicle.length))
+            .map(|offset| match vehicle.direction {
+                Direction::Horizontal | Direction::Free => (vehicle.row, vehicle.col + offset),
+                Direction::Vertical => (vehicle.row + offset, vehicle.col),
+            })
+            .collect::<Vec<_>>();
+
+        let fits = locations.iter().all(|location| self.contents
+            .get(location.0)
+            .and_then(|row| row.get(location.1))
+            .is_some_and(|tile| tile.empty())
+        );
+        if !fits {
+            return Err(Error::DoesNotFit(format!("vehicle {}", vehicle.id)));
         }
+
+        for (location, segments_left) in locations.into_iter().zip((0..vehicle.length).rev()) {
+            self.contents[location.0][location.1] = Vehicle(VehicleSegment {
+                id: vehicle.id,
+                direction: vehicle.direction.clone(),
+                // the segments of free pieces are all alike
+                segments_left: if vehicle.direction == Direction::Free {0} else {segments_left},
+                one_way: vehicle.one_way,
+            });
+        }
+        Ok(())
     }
 
 
@@ -543,22 +1570,146 @@ impl Board {
     }
 
 
-    /// Write the moves made on the `Board` to a file.
-    pub fn export(&self, file_path: &str) {
-        let turn_iterator: Vec<&Move> = successors(
+    /// Returns the moves made on the `Board`, in the order they were made.
+    pub fn history(&self) -> Vec<Move> {
+        let mut turns: Vec<Move> = successors(
             self.previous.deref().as_ref(), 
             |p: &&LinkedHistory| p.next_link.deref().as_ref()
-        ).map(|x| &x.last_move).collect();
+        ).map(|x| x.last_move.clone()).collect();
+
+        turns.reverse();
+        turns
+    }
+
+
+    /// Forget the moves made on the `Board`, making the current boardstate
+    /// the starting point of its history.
+    pub fn clear_history(&mut self) {
+        self.previous = Arc::new(None);
+    }
+
+
+    /// Write the moves made on the `Board` to a file.
+    pub fn export(&self, file_path: &str) {
+        fs::write(file_path, self.history_csv()).expect("Writing file failed.");
+    }
 
-        let mut writer = csv::Writer::from_path(file_path).expect("Making writer failed.");
-        writer.write_record(["car", "move"]).expect("Writing heading failed.");
 
-        for turn in turn_iterator.into_iter().rev() {
-            writer.serialize((&turn.get_id_string(), turn.direction)).expect("Writing entry failed.");
+    /// Returns the moves made on the `Board` in the csv format written by 
+    /// `Board.export`.
+    pub fn history_csv(&self) -> String {
+        // only the moves of free pieces have an axis
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .quote_style(csv::QuoteStyle::Never)
+            .from_writer(vec![]);
+        let history = self.history();
+        let mut heading = vec!["car", "move"];
+        if history.iter().any(|turn| turn.axis.is_some()) {
+            heading.push("axis");
+        }
+        writer.write_record(heading).expect("Writing heading failed.");
+
+        for turn in history {
+            let mut record = vec![turn.get_id_string(), turn.direction.to_string()];
+            if let Some(axis) = &turn.axis {
+                record.push(axis.to_str().to_string());
+            }
+            writer.write_record(record).expect("Writing entry failed.");
         }
         
-        writer.flush().expect("Flussing failed.");
+        String::from_utf8(writer.into_inner().expect("Flushing failed."))
+            .expect("Csv is not valid utf-8.")
+    }
+
+
+    /// Returns every vehicle on the `Board`, ordered by the location of their
+    /// top left segment. Free pieces are returned as a horizontal run of 
+    /// tiles per row, see `VehiclePlacement`.
+    pub fn vehicles(&self) -> Vec<VehiclePlacement> {
+        let mut found = HashSet::new();
+        let mut vehicles: Vec<VehiclePlacement> = vec![];
+
+        for (row, tiles) in self.contents.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Vehicle(vehicle) = tile {
+                    if vehicle.direction == Direction::Free {
+                        // a run continues on the tile after the previous one
+                        let continues = col > 0 && matches!(
+                            &tiles[col - 1], Vehicle(previous) if previous.id == vehicle.id
+                        );
+                        match vehicles.last_mut() {
+                            Some(run) if continues => run.length += 1,
+                            _ => vehicles.push(VehiclePlacement { 
+                                id: vehicle.id, 
+                                direction: Direction::Free, 
+                                col, 
+                                row, 
+                                length: 1,
+                                one_way: None,
+                            }),
+                        }
+                    // the first segment found is the front of the vehicle
+                    } else if found.insert(vehicle.id) {
+                        vehicles.push(VehiclePlacement { 
+                            id: vehicle.id, 
+                            direction: vehicle.direction.clone(), 
+                            col, 
+                            row, 
+                            length: vehicle.segments_left + 1,
+                            one_way: vehicle.one_way,
+                        });
+                    }
+                }
+            }
+        }
+        vehicles
+    }
+
+
+    /// Returns the current boardstate in the gameboard file format read by
+    /// `Board.fill`.
+    pub fn to_csv(&self) -> String {
+        // directives have fewer fields than vehicles, and vehicle names never
+        // need quotes, as `Board.fill_from_str` does not read them
+        let mut writer = csv::WriterBuilder::new()
+            .flexible(true)
+            .quote_style(csv::QuoteStyle::Never)
+            .from_writer(vec![]);
+        let vehicles = self.vehicles();
+        let mut heading = vec!["car", "orientation", "col", "row", "length"];
+        if vehicles.iter().any(|vehicle| vehicle.one_way.is_some()) {
+            heading.push("oneway");
+        }
+        writer.write_record(heading).expect("Writing heading failed.");
+
+        for vehicle in vehicles {
+            // gameboard files start counting at 1
+            let mut record = vec![
+                vehicle.id_string(),
+                vehicle.direction.to_str().to_string(),
+                (vehicle.col + 1).to_string(),
+                (vehicle.row + 1).to_string(),
+                vehicle.length.to_string(),
+            ];
+            if let Some(side) = vehicle.one_way {
+                record.push(side.to_str().to_string());
+            }
+            writer.write_record(record).expect("Writing entry failed.");
+        }
+        for directive in self.wall_directives().into_iter().chain(self.goal_directives()) {
+            writer.write_record(directive).expect("Writing directive failed.");
+        }
+
+        String::from_utf8(writer.into_inner().expect("Flushing failed."))
+            .expect("Csv is not valid utf-8.")
+    }
+
 
+    /// Write the current boardstate to a gameboard file, so it can be used
+    /// as the starting point of a new game.
+    pub fn save(&self, file_path: &str) {
+        fs::write(file_path, self.to_csv()).expect("Writing file failed.");
     }
 
 
@@ -571,8 +1722,143 @@ impl Board {
     }
 
 
+    /// Returns the board mirrored from top to bottom, with its exit and 
+    /// one-way vehicles mirrored along. The history is not kept.
+    pub fn mirrored(&self) -> Board {
+        let height = self.height();
+        let vehicles = self.vehicles().into_iter()
+            .map(|vehicle| VehiclePlacement {
+                row: match vehicle.direction {
+                    Direction::Vertical => height - vehicle.row - usize::from(vehicle.length),
+                    _ => height - 1 - vehicle.row,
+                },
+                one_way: vehicle.one_way.map(|side| side.mirrored()),
+                ..vehicle
+            })
+            .collect();
+        let walls = self.walls().into_iter()
+            .map(|(row, col)| (height - 1 - row, col))
+            .collect();
+        self.rebuilt(vehicles, walls, self.exit.map(|exit| exit.mirrored(height)))
+    }
+
+
+    /// Returns the board with every vehicle but the target renamed in the 
+    /// order they appear row by row, as "A", "B", ..., "Z", "AA", "AB" and 
+    /// so on, skipping the name of the target. The history is not kept.
+    pub fn relabeled(&self) -> Board {
+        let mut names = (1..)
+            .map(label_name)
+            .filter(|name| *name != self.target.name());
+        let mut labels = HashMap::from([(self.target, self.target)]);
+
+        let vehicles = self.vehicles().into_iter()
+            .map(|vehicle| VehiclePlacement {
+                id: *labels.entry(vehicle.id).or_insert_with(|| {
+                    let name = names.next().expect("Ran out of names.");
+                    VehicleId::new(&name).expect("Generated an invalid name.")
+                }),
+                ..vehicle
+            })
+            .collect();
+        self.rebuilt(vehicles, self.walls(), self.exit)
+    }
+
+
+    /// Returns `true` if mirroring the board from top to bottom keeps its 
+    /// exit in place, so the mirror image is the same puzzle. This is only 
+    /// the case for an exit on the middle row of a board with an odd number
+    /// of rows.
+    fn mirror_keeps_exit(&self) -> bool {
+        self.exit().is_ok_and(|exit| exit.mirrored(self.height()) == exit)
+    }
+
+
+    /// Returns the canonical form of the board: the relabeled board or its 
+    /// relabeled mirror image, whichever has the smallest text grid. Boards 
+    /// that only differ in the names of vehicles other than the target have
+    /// the same canonical form, like boards mirrored from top to bottom if 
+    /// the mirror keeps the exit row, see `Board.mirror_keeps_exit`.
+    pub fn canonical(&self) -> Board {
+        let relabeled = self.relabeled();
+        if !self.mirror_keeps_exit() {
+            return relabeled;
+        }
+        let mirrored = self.mirrored().relabeled();
+        if mirrored.to_string() < relabeled.to_string() {
+            mirrored
+        } else {
+            relabeled
+        }
+    }
+
+
+    /// Returns a hash of the canonical form of the board, see 
+    /// `Board.canonical`, without building it. Unlike `Board.get_hash` this 
+    /// includes the size and the exit of the board.
+    pub fn canonical_hash(&self) -> u64 {
+        let hash = self.labeling_hash(false);
+        if self.mirror_keeps_exit() {
+            hash.min(self.labeling_hash(true))
+        } else {
+            hash
+        }
+    }
+
+
+    /// Returns a hash of the board read row by row, from the top or from the
+    /// bottom when `mirror`ed, with the vehicles numbered in order of 
+    /// appearance instead of their names.
+    fn labeling_hash(&self, mirror: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.width(), self.height()).hash(&mut hasher);
+        let mut labels = HashMap::from([(self.target, 0)]);
+
+        let rows = if mirror {
+            Either::Left(self.contents.iter().rev())
+        } else {
+            Either::Right(self.contents.iter())
+        };
+        for tile in rows.flatten() {
+            match tile {
+                Vehicle(vehicle) => {
+                    let next_label = labels.len();
+                    let label = *labels.entry(vehicle.id).or_insert(next_label);
+                    // the segments left follow from the labels of the tiles
+                    let one_way = vehicle.one_way.map(|side| if mirror {side.mirrored()} else {side});
+                    (1u8, label, &vehicle.direction, one_way).hash(&mut hasher);
+                }
+                Wall => 2u8.hash(&mut hasher),
+                Empty => 0u8.hash(&mut hasher),
+            }
+        }
+
+        let exit = self.exit.map(|exit| if mirror {exit.mirrored(self.height())} else {exit});
+        exit.hash(&mut hasher);
+        hasher.finish()
+    }
+
+
+    /// Returns a board of the same size and target as this board, holding 
+    /// the given `vehicles`, `walls` and `exit` instead.
+    fn rebuilt(&self, vehicles: Vec<VehiclePlacement>, walls: Vec<(usize, usize)>, exit: Option<Exit>) -> Board {
+        let width = u8::try_from(self.width()).expect("Board is too large.");
+        let height = u8::try_from(self.height()).expect("Board is too large.");
+        let mut board = Board::with_size(width, height);
+        board.target = self.target;
+        board.exit = exit;
+        for wall in &walls {
+            board.add_wall(wall).expect("Wall does not fit on the board.");
+        }
+        for vehicle in &vehicles {
+            board.place(vehicle).expect("Vehicle does not fit on the board.");
+        }
+        board
+    }
+
+
     /// Return the location of the given vehicle on the board.
-    fn find_vehicle(&self, id_number: u8) -> Result<(usize, usize), ()> {
+    fn find_vehicle(&self, id_number: VehicleId) -> Result<(usize, usize), Error> {
         for numbered_row in self.contents.iter().enumerate() {
             let (row_num, row) = numbered_row;
 
@@ -585,6 +1871,474 @@ impl Board {
                 }
             }
         }
-        Result::Err(())
+        Result::Err(Error::NoVehicle(id_number.name()))
+    }
+
+
+    /// Returns the location of every tile of the given vehicle or piece on 
+    /// the board, in row-major order.
+    fn piece_locations(&self, id: VehicleId) -> Vec<(usize, usize)> {
+        self.contents.iter()
+            .enumerate()
+            .flat_map(|(row, tiles)| tiles.iter()
+                .enumerate()
+                .filter(move |(_, tile)| matches!(tile, Vehicle(vehicle) if vehicle.id == id))
+                .map(move |(col, _)| (row, col))
+            )
+            .collect()
+    }
+
+
+    /// Adds the moves of every free piece to `moves`. A free piece slides 
+    /// along both axes as long as all its tiles land on empty tiles or on 
+    /// tiles of the piece itself.
+    fn free_piece_moves(&self, moves: &mut Vec<Move>) {
+        let mut pieces: Vec<VehicleId> = vec![];
+        for tile in self.contents.iter().flatten() {
+            if let Vehicle(vehicle) = tile {
+                if vehicle.direction == Direction::Free && !pieces.contains(&vehicle.id) {
+                    pieces.push(vehicle.id);
+                }
+            }
+        }
+
+        for id in pieces {
+            let locations = self.piece_locations(id);
+            for (axis, sign) in [(Direction::Horizontal, 1), (Direction::Horizontal, -1), (Direction::Vertical, 1), (Direction::Vertical, -1)] {
+                for distance in 1i8.. {
+                    let direction = sign * distance;
+                    let fits = locations.iter().all(|location| {
+                        let target = match axis {
+                            Direction::Horizontal => location.1.checked_add_signed(direction.into()).map(|col| (location.0, col)),
+                            _ => location.0.checked_add_signed(direction.into()).map(|row| (row, location.1)),
+                        };
+                        match target.and_then(|(row, col)| self.contents.get(row)?.get(col)) {
+                            Some(Empty) => true,
+                            Some(Vehicle(vehicle)) => vehicle.id == id,
+                            _ => false,
+                        }
+                    });
+                    if !fits {
+                        break;
+                    }
+                    moves.push(Move { vehicle_id: id, direction, axis: Some(axis.clone()) });
+                }
+            }
+        }
+    }
+}
+
+
+/// Parses a `Board` from a text grid, with one line per row, `.` for an empty
+/// tile, `x` for a wall and the name of the vehicle on every other tile:
+/// 
+/// ```text
+/// .AABBB
+/// .CCED.
+/// XXGE.I
+/// FFGHHI
+/// K.L.JJ
+/// K.L...
+/// ```
+/// 
+/// Rows with vehicle names longer than a single letter must separate their 
+/// tiles with whitespace. Empty lines and whitespace around rows are ignored.
+/// Lines starting with "#" are directives like "#exit top 3", see 
+/// `Board.goal_directives`.
+impl FromStr for Board {
+    type Err = Error;
+
+    fn from_str(grid: &str) -> Result<Self, Self::Err> {
+        let (directives, rows): (Vec<_>, Vec<_>) = grid.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .partition(|line| line.starts_with('#'));
+        let rows = rows.into_iter()
+            .map(|line| if line.contains(char::is_whitespace) {
+                line.split_whitespace().map(String::from).collect::<Vec<_>>()
+            } else {
+                line.chars().map(String::from).collect()
+            })
+            .collect::<Vec<_>>();
+
+        let too_large = |_| Error::Parse(String::from("a grid of more than 255 rows or columns"));
+        let height = u8::try_from(rows.len()).map_err(too_large)?;
+        let width = u8::try_from(rows.first().map_or(0, Vec::len)).map_err(too_large)?;
+        if rows.iter().any(|row| row.len() != usize::from(width)) {
+            return Err(Error::Parse(String::from("a grid with rows of different lengths")));
+        }
+        let tiles = rows.into_iter().flatten().collect::<Vec<_>>();
+        let walls = tiles.iter()
+            .enumerate()
+            .filter(|(_, name)| *name == GRID_WALL)
+            .map(|(index, _)| (index / usize::from(width), index % usize::from(width)))
+            .collect::<Vec<_>>();
+        let tiles = tiles.into_iter()
+            .map(|name| (name != GRID_EMPTY && name != GRID_WALL).then_some(name))
+            .collect();
+
+        let mut board = Board::from_tiles(width, height, tiles)?;
+        for wall in &walls {
+            board.add_wall(wall)?;
+        }
+        for directive in directives {
+            board.apply_directive(&directive.split_whitespace().collect::<Vec<_>>())?;
+        }
+        Ok(board)
+    }
+}
+
+
+/// Formats the `Board` as the text grid read by `Board::from_str`. Tiles are
+/// separated by spaces when a vehicle name is longer than a single letter.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = self.contents.iter()
+            .map(|row| row.iter()
+                .map(|tile| match tile {
+                    Vehicle(vehicle) => vehicle.id_string(),
+                    Wall => String::from(GRID_WALL),
+                    Empty => String::from(GRID_EMPTY),
+                })
+                .collect::<Vec<_>>()
+            )
+            .collect::<Vec<_>>();
+        let width = names.iter().flatten().map(|name| name.chars().count()).max().unwrap_or(1);
+
+        for row in names {
+            let line = if width == 1 {
+                row.concat()
+            } else {
+                row.iter()
+                    .map(|name| format!("{name:width$}"))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        let directives = self.free_directives().into_iter()
+            .chain(self.one_way_directives())
+            .chain(self.goal_directives());
+        for directive in directives {
+            writeln!(f, "{}", directive.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A text grid using every feature of the format: walls, names longer 
+    /// than a letter, a one-way vehicle, a free piece shaped like a bar and
+    /// an exit.
+    const GRID: &str = "\
+AA  AA  .   x
+B   .   CAR CAR
+B   X   X   .
+F   F   .   .
+#free F
+#oneway B bottom
+#exit right 3
+";
+
+
+    #[test]
+    fn grid_round_trip() {
+        let board: Board = GRID.parse().unwrap();
+        assert_eq!(board.walls(), vec![(0, 3)]);
+        assert_eq!(board.to_string(), GRID);
+    }
+
+
+    #[test]
+    fn csv_round_trip() {
+        let board: Board = GRID.parse().unwrap();
+        let mut read = Board::new(4);
+        read.fill_from_str(&board.to_csv()).unwrap();
+        assert_eq!(read.to_string(), GRID);
+    }
+
+
+    #[test]
+    fn json_round_trip() {
+        let board: Board = GRID.parse().unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        let read: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.to_string(), GRID);
+    }
+
+
+    #[test]
+    fn gameboard_files_round_trip() {
+        let files = [
+            include_str!("../gameboards/Rushhour6x6_1.csv"),
+            include_str!("../gameboards/Rushhour9x9_4.csv"),
+            include_str!("../gameboards/Klotski4x5_1.csv"),
+        ];
+        for (contents, (width, height)) in files.into_iter().zip([(6, 6), (9, 9), (4, 5)]) {
+            let mut board = Board::with_size(width, height);
+            board.fill_from_str(contents).unwrap();
+            let grid: Board = board.to_string().parse().unwrap();
+            assert_eq!(grid.to_csv(), board.to_csv());
+        }
+    }
+
+
+    #[test]
+    fn names_of_grid_tiles_are_rejected() {
+        for name in [GRID_WALL, GRID_EMPTY, "#A", "", "A B", "A,B"] {
+            assert!(VehicleId::new(name).is_err(), "{name:?} was accepted");
+        }
+        assert!(VehicleId::new("R&D").is_ok());
+
+        let mut board = Board::new(6);
+        assert!(board.fill_from_str("car,orientation,col,row,length\nx,H,1,1,2\n").is_err());
+    }
+
+
+    #[test]
+    fn invalid_csv_lines_are_errors() {
+        for line in ["A,Q,1,1,2", "A,H,0,1,2", "A,H,1,1", "A,H,6,1,2", "A,V,1,1,2,left", "#wall,7,1"] {
+            let mut board = Board::new(6);
+            let contents = format!("car,orientation,col,row,length\n{line}\n");
+            assert!(board.fill_from_str(&contents).is_err(), "{line:?} was accepted");
+        }
+    }
+
+
+    /// Returns the moves of the vehicle `name` on `board` as typed in manual
+    /// games, sorted.
+    fn distances(board: &Board, name: &str) -> Vec<String> {
+        let id = VehicleId::new(name).unwrap();
+        let mut distances = board.possible_moves().unwrap_or_default().into_iter()
+            .filter(|veh_move| veh_move.vehicle_id == id)
+            .map(|veh_move| veh_move.distance_string())
+            .collect::<Vec<_>>();
+        distances.sort();
+        distances
+    }
+
+
+    #[test]
+    fn pieces_that_are_not_bars_are_free() {
+        let board: Board = "AA..\nA...\n..BB\n".parse().unwrap();
+        assert_eq!(board.vehicles()[0].direction, Direction::Free);
+        assert_eq!(board.vehicles().last().unwrap().direction, Direction::Horizontal);
+        // a piece in two parts is not a piece
+        assert!("A.A.\n....\n".parse::<Board>().is_err());
+    }
+
+
+    #[test]
+    fn free_pieces_slide_along_both_axes() {
+        let board: Board = "AA..\nAA..\n...x\n....\n".parse().unwrap();
+        assert_eq!(distances(&board, "A"), ["h1", "h2", "v1", "v2"]);
+
+        // the piece may move over its own tiles, but not over walls or vehicles
+        let board: Board = "AA.\nA..\nx..\n".parse().unwrap();
+        assert_eq!(distances(&board, "A"), ["h1"]);
+        let board: Board = "AA.\nA..\nBB.\n".parse().unwrap();
+        assert_eq!(distances(&board, "A"), ["h1"]);
+        let board: Board = "AA.\nA..\n...\n".parse().unwrap();
+        assert_eq!(distances(&board, "A"), ["h1", "v1"]);
+    }
+
+
+    #[test]
+    fn free_piece_moves_keep_the_shape() {
+        let mut board: Board = "AA..\nA...\n....\n".parse().unwrap();
+        let moves = board.possible_moves().unwrap();
+        let right = moves.iter().find(|veh_move| veh_move.distance_string() == "h2").unwrap();
+        board.try_move_vehicle(right.clone()).unwrap();
+        let down = Move { vehicle_id: right.vehicle_id, direction: 1, axis: Some(Direction::Vertical) };
+        board.try_move_vehicle(down).unwrap();
+
+        assert_eq!(board.to_string(), "....\n..AA\n..A.\n");
+        assert_eq!(board.history_csv(), "car,move,axis\nA,2,H\nA,1,V\n");
+    }
+
+
+    #[test]
+    fn free_bars_stay_free() {
+        let board: Board = "FF.\n...\n#free F\n".parse().unwrap();
+        assert_eq!(distances(&board, "F"), ["h1", "v1"]);
+        let mut read = Board::with_size(3, 2);
+        read.fill_from_str(&board.to_csv()).unwrap();
+        assert_eq!(distances(&read, "F"), ["h1", "v1"]);
+    }
+
+
+    /// Asserts that every board in `boards` has the same canonical form and
+    /// the same canonical hash.
+    fn assert_same_canonical(boards: &[Board]) {
+        let canonical = boards[0].canonical().to_string();
+        let hash = boards[0].canonical_hash();
+        for board in boards {
+            assert_eq!(board.canonical().to_string(), canonical, "{board}");
+            assert_eq!(board.canonical_hash(), hash, "{board}");
+        }
+    }
+
+
+    #[test]
+    fn relabeled_boards_are_the_same() {
+        let board: Board = GRID.parse().unwrap();
+        let swapped: Board = GRID.replace('B', "@").replace('F', "B").replace('@', "F")
+            .replace("CAR", "Q  ")
+            .parse().unwrap();
+        assert_same_canonical(&[board.clone(), board.relabeled(), swapped]);
+
+        let mut board = Board::with_size(6, 6);
+        board.fill_from_str(include_str!("../gameboards/Rushhour6x6_1.csv")).unwrap();
+        assert_same_canonical(&[board.clone(), board.relabeled(), board.canonical()]);
+    }
+
+
+    #[test]
+    fn mirrored_boards_are_the_same_if_the_exit_row_is_kept() {
+        let board: Board = "\
+A A . . .
+B . . C .
+B X X C .
+. . . . D
+. . . . D
+#oneway B top
+".parse().unwrap();
+        assert_eq!(board.mirrored().exit(), board.exit());
+        assert_same_canonical(&[
+            board.clone(),
+            board.relabeled(),
+            board.mirrored(),
+            board.mirrored().relabeled(),
+            board.canonical().mirrored(),
+        ]);
+    }
+
+
+    #[test]
+    fn different_boards_are_not_the_same() {
+        let board: Board = GRID.parse().unwrap();
+        let others = [
+            GRID.replace("#exit right 3", "#exit right 2"),
+            GRID.replace("#oneway B bottom", "#oneway B top"),
+            GRID.replace("#free F\n", ""),
+            GRID.replace("B   X   X   .", "B   .   X   X"),
+            // the mirror moves the exit to another row
+            board.mirrored().to_string(),
+        ];
+        for other in others {
+            let other: Board = other.parse().unwrap();
+            assert_ne!(other.canonical().to_string(), board.canonical().to_string(), "{other}");
+            assert_ne!(other.canonical_hash(), board.canonical_hash(), "{other}");
+        }
+
+        let mut board = Board::with_size(6, 6);
+        board.fill_from_str(include_str!("../gameboards/Rushhour6x6_1.csv")).unwrap();
+        assert_ne!(board.mirrored().canonical_hash(), board.canonical_hash());
+    }
+
+
+    #[test]
+    fn exits_must_be_on_the_side_of_the_board() {
+        let grid = "X X . .\n. . . .\n. . . .\n";
+        assert!(format!("{grid}#exit right 3").parse::<Board>().is_ok());
+        assert!(format!("{grid}#exit right 4").parse::<Board>().is_err());
+        assert!(format!("{grid}#exit top 4").parse::<Board>().is_ok());
+        assert!(format!("{grid}#exit bottom 5").parse::<Board>().is_err());
+
+        let mut board: Board = grid.parse().unwrap();
+        assert!(board.set_exit(Some(Exit { side: Side::Left, position: 3 })).is_err());
+        assert!(board.set_exit(Some(Exit { side: Side::Top, position: 3 })).is_ok());
+
+        let json = serde_json::to_string(&board).unwrap().replace(r#""position":3"#, r#""position":9"#);
+        assert!(serde_json::from_str::<Board>(&json).is_err());
+    }
+
+
+    #[test]
+    fn targets_win_at_any_exit() {
+        let won = |grid: &str| grid.parse::<Board>().unwrap().is_won();
+        // left and top exits
+        assert_eq!(won("X X . .\n. . . .\n#exit left 1"), Ok(true));
+        assert_eq!(won(". X X .\n. . . .\n#exit left 1"), Ok(false));
+        assert_eq!(won("X X . .\n. . . .\n#exit left 2"), Ok(false));
+        assert_eq!(won("X . .\nX . .\n. . .\n#exit top 1"), Ok(true));
+        assert_eq!(won(". . .\nX . .\nX . .\n#exit top 1"), Ok(false));
+        // a vertical target leaves at the bottom of its column by default
+        assert_eq!(won(". . .\n. X .\n. X .\n"), Ok(true));
+        assert_eq!(won(". X .\n. X .\n. . .\n"), Ok(false));
+        // longer targets
+        assert_eq!(won(". X X X\n. . . .\n"), Ok(true));
+        assert_eq!(won("X X X .\n. . . .\n"), Ok(false));
+        assert_eq!(won("T T T .\n. . . .\n#target T\n#exit left 1"), Ok(true));
+    }
+
+
+    #[test]
+    fn targets_that_cannot_reach_the_exit_are_errors() {
+        let won = |grid: &str| grid.parse::<Board>().unwrap().is_won();
+        assert_eq!(won("X X . .\n. . . .\n#exit top 1"), Err(Error::InvalidGoal));
+        assert_eq!(won("X . .\nX . .\n. . .\n#exit right 1"), Err(Error::InvalidGoal));
+        assert_eq!(won("A A . .\n. . . .\n"), Err(Error::NoVehicle(String::from("X"))));
+    }
+
+
+    #[test]
+    fn json_leaves_out_the_history() {
+        let mut board: Board = "X X . .\n. . . .\n".parse().unwrap();
+        board.try_move_vehicle(Move::new("X", 1).unwrap()).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert!(!json.contains("history"));
+
+        let read: Board = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.to_string(), ".XX.\n....\n");
+        assert!(read.history().is_empty());
+    }
+
+
+    #[test]
+    fn one_way_vehicles_only_drive_forward() {
+        let two_way: Board = ". A A .\n. . . B\n. . . B\n. . . .\n".parse().unwrap();
+        assert_eq!(distances(&two_way, "A"), ["-1", "1"]);
+        assert_eq!(distances(&two_way, "B"), ["-1", "1"]);
+
+        let one_way: Board = format!("{two_way}#oneway A right\n#oneway B top\n").parse().unwrap();
+        assert_eq!(distances(&one_way, "A"), ["1"]);
+        assert_eq!(distances(&one_way, "B"), ["-1"]);
+        assert!(one_way.clone().try_move_vehicle(Move::new("A", -1).unwrap()).is_err());
+
+        // a one-way vehicle can't drive sideways
+        assert!(format!("{two_way}#oneway A top\n").parse::<Board>().is_err());
+    }
+
+
+    #[test]
+    fn one_way_vehicles_round_trip() {
+        let grid = ". A A .\n. . . B\n. . . B\n. . . .\n#oneway A right\n#oneway B top\n";
+        let board: Board = grid.parse().unwrap();
+        assert_eq!(board.to_string().replace(' ', ""), grid.replace(' ', ""));
+
+        let csv = board.to_csv();
+        assert!(csv.lines().any(|line| line == "A,H,2,1,2,right"));
+        let mut read = Board::new(4);
+        read.fill_from_str(&csv).unwrap();
+        assert_eq!(distances(&read, "A"), ["1"]);
+        assert_eq!(distances(&read, "B"), ["-1"]);
+        assert_eq!(read.to_string(), board.to_string());
+    }
+
+
+    #[test]
+    fn boards_without_moves_have_no_solution() {
+        let board: Board = "X X B\n. . B\n. . .\n#oneway B top\n".parse().unwrap();
+        assert_eq!(board.possible_moves(), Err(Error::NoMoves));
+
+        let mut solver = crate::solvers::Solver::from_board(board, usize::MAX);
+        solver.set_show_progress(false);
+        assert!(solver.breadth_first().unwrap().is_none());
+        assert!(solver.uniform_cost().unwrap().is_none());
     }
 }
@@ -0,0 +1,288 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::Board;
+use crate::error::Error;
+use crate::solvers::Solver;
+
+
+/// A puzzle in a `Collection`, with its metadata.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// Name used to find the puzzle, eg by the `manual` command.
+    pub name: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<String>,
+    /// Number of moves of the shortest solution, if known.
+    #[serde(default)]
+    pub optimal_length: Option<usize>,
+    /// The starting position of the puzzle, which also holds its size.
+    pub board: Board,
+}
+
+
+/// Many puzzles stored together in a single JSON file, as an alternative to
+/// a gameboard file per puzzle.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Collection {
+    pub name: String,
+    pub puzzles: Vec<Puzzle>,
+}
+
+
+impl Collection {
+    /// Read a collection from a JSON file.
+    ///
+    /// Returns an error if the file can't be read or is not a collection.
+    pub fn load(file_path: &str) -> Result<Collection, Error> {
+        let contents = fs::read_to_string(file_path)
+            .map_err(|error| Error::Io(format!("{file_path}: {error}")))?;
+        serde_json::from_str(&contents)
+            .map_err(|error| Error::Parse(format!("collection {file_path}: {error}")))
+    }
+
+
+    /// Write the collection to a JSON file.
+    pub fn save(&self, file_path: &str) {
+        let contents = serde_json::to_string_pretty(self).expect("Serializing collection failed.");
+        fs::write(file_path, contents).expect("Writing collection failed.");
+    }
+
+
+    /// Returns the puzzle called `name`, or else the first puzzle whose name
+    /// contains `name`.
+    pub fn find(&self, name: &str) -> Option<&Puzzle> {
+        self.puzzles.iter()
+            .find(|puzzle| puzzle.name == name)
+            .or_else(|| self.puzzles.iter().find(|puzzle| puzzle.name.contains(name)))
+    }
+}
+
+
+/// How alike the boards of a `DuplicateGroup` are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Likeness {
+    /// The same vehicles at the same positions, with the same goal.
+    Identical,
+    /// The same board after renaming vehicles, or mirroring it if that keeps
+    /// the exit row, see `Board.canonical`.
+    Isomorphic,
+    /// Boards in the same state-space cluster: one of them can be reached
+    /// from the other by moving vehicles. Boards with one-way vehicles are
+    /// never grouped in a cluster, as their moves can't always be undone.
+    Cluster,
+}
+
+
+/// Boards found to be duplicates of each other by `find_duplicates`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub likeness: Likeness,
+    /// Names of the boards in the group.
+    pub boards: Vec<String>,
+}
+
+
+/// Outcome of `find_duplicates`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Duplicates {
+    /// Every group of duplicates. Boards in an isomorphic group are not all
+    /// identical, and boards in a cluster group are not all isomorphic.
+    pub groups: Vec<DuplicateGroup>,
+    /// Names of the boards with too many reachable boards to search their 
+    /// cluster for other boards.
+    pub unexplored: Vec<String>,
+}
+
+
+/// Returns the indices of the items with equal keys in groups, in the order
+/// the keys first appear.
+fn group_by<K: Hash + Eq>(keys: impl IntoIterator<Item = K>) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = vec![];
+    let mut group_of_key = HashMap::new();
+
+    for (index, key) in keys.into_iter().enumerate() {
+        let group = *group_of_key.entry(key).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[group].push(index);
+    }
+    groups
+}
+
+
+/// Finds the `boards` that are identical, isomorphic or in the same 
+/// state-space cluster, given as (name, board) pairs.
+/// 
+/// Clusters are searched only between boards with the same size and pieces,
+/// and only up to `max_states` reachable boards per board. Boards with 
+/// one-way vehicles are left out of the clusters: one of them reaching 
+/// another does not mean it can be reached back.
+pub fn find_duplicates(boards: &[(String, Board)], max_states: usize) -> Duplicates {
+    let mut duplicates = Duplicates::default();
+    let names_of = |classes: &[&Vec<usize>]| -> Vec<String> {
+        classes.iter()
+            .flat_map(|class| class.iter().map(|&index| boards[index].0.clone()))
+            .collect()
+    };
+
+    // every class holds the indices of identical boards
+    let identical = group_by(boards.iter().map(|(_, board)| (board.get_hash(), board.exit().ok(), board.target())));
+    for class in identical.iter().filter(|class| class.len() > 1) {
+        duplicates.groups.push(DuplicateGroup { likeness: Likeness::Identical, boards: names_of(&[class]) });
+    }
+
+    // every isomorphic group holds the indices of identical classes
+    let isomorphic = group_by(identical.iter().map(|class| boards[class[0]].1.canonical_hash()));
+    for group in isomorphic.iter().filter(|group| group.len() > 1) {
+        let classes = group.iter().map(|&class| &identical[class]).collect::<Vec<_>>();
+        duplicates.groups.push(DuplicateGroup { likeness: Likeness::Isomorphic, boards: names_of(&classes) });
+    }
+
+    // only boards with the same size and pieces can be in the same cluster
+    let representatives = isomorphic.iter()
+        .map(|group| &boards[identical[group[0]][0]])
+        .collect::<Vec<_>>();
+    let families = group_by(representatives.iter().map(|(_, board)| {
+        let mut pieces = board.vehicles().into_iter()
+            .map(|vehicle| (vehicle.direction.to_str(), vehicle.length, vehicle.one_way.is_some()))
+            .collect::<Vec<_>>();
+        pieces.sort();
+        (board.width(), board.height(), board.walls().len(), pieces)
+    }));
+
+    let one_way = |family: &&Vec<usize>| representatives[family[0]].1.vehicles()
+        .iter()
+        .any(|vehicle| vehicle.one_way.is_some());
+    for family in families.iter().filter(|family| family.len() > 1 && !one_way(family)) {
+        // the cluster of every member of the family, merged when one member
+        // is reached from another
+        let mut cluster_of = (0..family.len()).collect::<Vec<_>>();
+        let mut explored: Vec<(usize, HashSet<u64>)> = vec![];
+
+        for (member, &group) in family.iter().enumerate() {
+            let (name, board) = representatives[group];
+            let hash = board.canonical_hash();
+            let reached_by = explored.iter()
+                .filter(|(_, states)| states.contains(&hash))
+                .map(|(other, _)| cluster_of[*other])
+                .collect::<Vec<_>>();
+            if !reached_by.is_empty() {
+                for cluster in cluster_of.iter_mut().filter(|cluster| reached_by.contains(cluster)) {
+                    *cluster = member;
+                }
+                cluster_of[member] = member;
+                continue;
+            }
+
+            let mut solver = Solver::from_board(board.clone(), usize::MAX);
+            solver.set_canonical(true);
+            solver.set_show_progress(false);
+            match solver.reachable_states(max_states) {
+                Some(states) => explored.push((member, states)),
+                None => duplicates.unexplored.push(name.clone()),
+            }
+        }
+
+        for cluster in group_by(cluster_of).iter().filter(|cluster| cluster.len() > 1) {
+            let classes = cluster.iter()
+                .flat_map(|&member| isomorphic[family[member]].iter().map(|&class| &identical[class]))
+                .collect::<Vec<_>>();
+            duplicates.groups.push(DuplicateGroup { likeness: Likeness::Cluster, boards: names_of(&classes) });
+        }
+    }
+    duplicates
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "\
+. . . . . .
+. C C . . .
+. . B . . .
+X X B . . .
+. . B . . .
+. . . . . .
+";
+
+
+    /// `START` after moving the C car to the left.
+    const MOVED: &str = "\
+. . . . . .
+C C . . . .
+. . B . . .
+X X B . . .
+. . B . . .
+. . . . . .
+";
+
+
+    /// Returns the likeness and board names of every group found among the
+    /// named `boards`.
+    fn groups(boards: &[(&str, String)]) -> Vec<(Likeness, Vec<String>)> {
+        let boards = boards.iter()
+            .map(|(name, board)| (name.to_string(), board.parse().unwrap()))
+            .collect::<Vec<_>>();
+        let duplicates = find_duplicates(&boards, 1000);
+        assert!(duplicates.unexplored.is_empty());
+        duplicates.groups.into_iter()
+            .map(|group| (group.likeness, group.boards))
+            .collect()
+    }
+
+
+    /// Returns the names as owned strings.
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+
+    #[test]
+    fn duplicates_are_grouped_by_likeness() {
+        let boards = [
+            ("start", START.to_string()),
+            ("copy", START.to_string()),
+            ("renamed", START.replace('C', "D")),
+            ("moved", MOVED.to_string()),
+            ("other", START.replace(". C C", "C C C")),
+        ];
+        assert_eq!(groups(&boards), vec![
+            (Likeness::Identical, names(&["start", "copy"])),
+            (Likeness::Isomorphic, names(&["start", "copy", "renamed"])),
+            (Likeness::Cluster, names(&["start", "copy", "renamed", "moved"])),
+        ]);
+    }
+
+
+    #[test]
+    fn boards_with_other_goals_are_not_identical() {
+        let boards = [
+            ("start", START.to_string()),
+            ("exit below", format!("{START}#exit bottom 2\n")),
+        ];
+        assert!(!groups(&boards).iter().any(|(likeness, _)| matches!(likeness, Likeness::Identical | Likeness::Isomorphic)));
+    }
+
+
+    #[test]
+    fn one_way_boards_are_kept_out_of_clusters() {
+        let boards = [
+            ("start", format!("{START}#oneway C left\n")),
+            ("copy", format!("{START}#oneway C left\n")),
+            ("moved", format!("{MOVED}#oneway C left\n")),
+        ];
+        assert_eq!(groups(&boards), vec![
+            (Likeness::Identical, names(&["start", "copy"])),
+        ]);
+    }
+}
@@ -0,0 +1,402 @@
+//! The commands of the `rusthour` binary, run by `main` once the command
+//! line is parsed.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::process::exit;
+use std::time::{Duration, Instant};
+
+use indicatif::ProgressIterator;
+use rusthour::{convert, html, notation, svg};
+use rusthour::board::{Board, Move};
+use rusthour::collection::{find_duplicates, Collection, Likeness, Puzzle};
+use rusthour::convert::BoardFormat;
+use rusthour::error::Error;
+use rusthour::solvers::{CostModel, Goal, Solution, Solver};
+
+use crate::ui::{play, replay, resume, verify, board_name, find_board, list_collections, load_boards, open_board, read_source, result_file_name, STDIN, Format, Score};
+use crate::{Collect, Convert, Database, Dedupe, Html, Manual, Replay, Solve, Svg, Verify};
+
+
+/// Play a gameboard, or continue a saved game, as requested by the `manual`
+/// command.
+pub fn play_manually(input: &Manual) {
+    match input {
+        Manual { resume: Some(save_file), .. } => report_score(resume(save_file)),
+        Manual { board_name: Some(board_name), .. } => {
+            println!("Searching board {}", board_name);
+            let (name, board) = find_or_panic(board_name);
+            report_score(play(&name, &board));
+        }
+        _ => unreachable!("Clap requires a board name or save file."),
+    }
+}
+
+
+/// Solve a gameboard, or all of them, as requested by the `solve` command.
+pub fn solve(input: &Solve) {
+    let Solve { board_name, all, goal, partial, cost, weight, canonical, max_depth, format } = input;
+    if *all {
+        return solve_all(*max_depth, *format);
+    }
+    let Some(board_name) = board_name else {
+        unreachable!("Clap requires a board name or --all.");
+    };
+
+    let (name, board) = find_or_panic(board_name);
+    let cost_model = cost.model(&board, weight)
+        .unwrap_or_else(|error| panic!("Invalid --weight, {error}."));
+    let goal = match goal {
+        None => Goal::Exit,
+        Some(goal_name) if *partial => Goal::Vehicles(find_or_panic(goal_name).1.vehicles()),
+        Some(goal_name) => Goal::Board(Box::new(find_or_panic(goal_name).1)),
+    };
+    let solution = solve_board(name, board, *max_depth, goal, cost_model, *canonical);
+
+    match (format, &solution.moves) {
+        (Format::Json, _) => println!(
+            "{}", serde_json::to_string_pretty(&solution).expect("Serializing solution failed.")
+        ),
+        (Format::Text, Some(moves)) => match solution.cost {
+            Some(cost) => println!(
+                "Found a solution of {} moves costing {}, written to results/solution.csv.", 
+                moves.len(), cost
+            ),
+            None => println!(
+                "Found a solution of {} moves, written to results/solution.csv.", 
+                moves.len()
+            ),
+        },
+        (Format::Text, None) => println!("No solution found."),
+    }
+}
+
+
+/// Check a solution of a gameboard, as requested by the `verify` command.
+pub fn verify_solution(input: &Verify) {
+    let (name, board) = find_or_panic(&input.board_name);
+    let verification = verify(&name, board, &input.solution)
+        .unwrap_or_else(|error| panic!("Reading {} failed: {error}.", input.solution));
+
+    match (input.format, verification.invalid_move) {
+        (Format::Json, _) => println!(
+            "{}", serde_json::to_string_pretty(&verification).expect("Serializing result failed.")
+        ),
+        (Format::Text, Some(turn)) => println!("Move {turn} of the solution is not possible."),
+        (Format::Text, None) if verification.solved => println!(
+            "The solution is valid and solves the board in {} moves.", verification.moves
+        ),
+        (Format::Text, None) => println!("The moves are valid, but don't solve the board."),
+    }
+}
+
+
+/// Step through a solution of a gameboard, as requested by the `replay` 
+/// command.
+pub fn replay_solution(input: &Replay) {
+    let (_, board) = find_or_panic(&input.board_name);
+    let delay = input.delay.map(Duration::from_millis);
+
+    if let Err(error) = replay(board, &input.solution, delay) {
+        panic!("Replaying {} failed: {error}.", input.solution);
+    }
+}
+
+
+/// Returns the name and board of the gameboard file or collection puzzle 
+/// `board_name`, see `open_board`.
+fn find_or_panic(board_name: &str) -> (String, Board) {
+    if let Some(found) = open_board(board_name) {
+        return found;
+    }
+    if board_name == STDIN || Path::new(board_name).is_file() {
+        panic!(
+            "{board_name} is not a valid gameboard. The size of a csv gameboard is taken from its \
+            file name, like Rushhour6x6_1.csv, or else has to be given with --size."
+        );
+    }
+    match find_board(board_name) {
+        Some(filename) => panic!("{filename} is not a valid gameboard."),
+        None => panic!("No board named {} found.", board_name),
+    }
+}
+
+
+/// Find the shortest solution of `board` reaching `goal`, or the cheapest
+/// one for any `cost_model` but `CostModel::Slides`, and export it to 
+/// results/solution.csv. See `Solver.set_canonical` for `canonical`.
+fn solve_board(name: String, board: Board, max_depth: usize, goal: Goal, cost_model: CostModel, canonical: bool) -> Solution {
+    let mut solver = Solver::from_board(board, usize::MAX);
+    solver.set_max_depth(max_depth);
+    solver.set_goal(goal);
+    solver.set_canonical(canonical);
+    let counts_slides = matches!(cost_model, CostModel::Slides);
+    solver.set_cost_model(cost_model.clone());
+
+    let start_time = Instant::now();
+    let solved = if counts_slides {
+        solver.breadth_first()
+    } else {
+        solver.uniform_cost()
+    };
+    let solved = match solved {
+        Ok(solved) => solved,
+        Err(error) => panic!("Solving failed, the board {name} is invalid: {error}."),
+    };
+    if let Some(solved) = &solved {
+        solved.export("results/solution.csv");
+    }
+    Solution {
+        board: name,
+        seconds: start_time.elapsed().as_secs_f64(),
+        cost: solved.as_ref()
+            .filter(|_| !counts_slides)
+            .map(|solved| cost_model.total(&solved.history())),
+        moves: solved.map(|solved| solved.history()),
+    }
+}
+
+
+/// Solve every gameboard file and every collection puzzle, as requested by
+/// `solve --all`. The optimal lengths found are stored in the collections.
+fn solve_all(max_depth: usize, format: Format) {
+    let mut solutions = vec![];
+
+    for (filename, board) in load_boards() {
+        solutions.push(solve_board(board_name(&filename).unwrap_or(filename), board, max_depth, Goal::Exit, CostModel::Slides, false));
+    }
+
+    for (path, mut collection) in list_collections() {
+        for puzzle in &mut collection.puzzles {
+            let mut board = puzzle.board.clone();
+            board.clear_history();
+            let solution = solve_board(puzzle.name.clone(), board, max_depth, Goal::Exit, CostModel::Slides, false);
+            // a search cut off by max_depth proves nothing about longer solutions
+            if let Some(moves) = &solution.moves {
+                puzzle.optimal_length = Some(moves.len());
+            }
+            solutions.push(solution);
+        }
+        collection.save(&path);
+    }
+
+    match format {
+        Format::Json => println!(
+            "{}", serde_json::to_string_pretty(&solutions).expect("Serializing solutions failed.")
+        ),
+        Format::Text => for solution in solutions {
+            match solution.moves {
+                Some(moves) => println!("{}: {} moves in {:.2}s", solution.board, moves.len(), solution.seconds),
+                None => println!("{}: no solution found", solution.board),
+            }
+        },
+    }
+}
+
+
+/// Write every gameboard file into a single collection, as requested by the
+/// `collect` command.
+pub fn collect_boards(input: &Collect) {
+    let mut collection = Collection {
+        name: input.name.clone().unwrap_or_else(|| {
+            let file_name = input.output.rsplit('/').next().unwrap_or(&input.output);
+            file_name.strip_suffix(".json").unwrap_or(file_name).to_string()
+        }),
+        puzzles: vec![],
+    };
+
+    let mut forms = HashSet::new();
+    for (filename, board) in load_boards() {
+        if input.unique && !forms.insert(board.canonical().to_string()) {
+            println!("Skipped {filename}, a copy of an earlier gameboard.");
+            continue;
+        }
+        collection.puzzles.push(Puzzle {
+            name: board_name(&filename).unwrap_or(filename.clone()),
+            author: None,
+            difficulty: None,
+            optimal_length: None,
+            board,
+        });
+    }
+
+    collection.save(&input.output);
+    println!("Written {} puzzles to {}.", collection.puzzles.len(), input.output);
+}
+
+
+/// Report the duplicate puzzles among every gameboard file and collection
+/// puzzle, as requested by the `dedupe` command.
+pub fn dedupe_boards(input: &Dedupe) {
+    let mut boards = vec![];
+    for (filename, board) in load_boards() {
+        boards.push((board_name(&filename).unwrap_or(filename), board));
+    }
+    for (_, collection) in list_collections() {
+        for puzzle in collection.puzzles {
+            boards.push((format!("{}/{}", collection.name, puzzle.name), puzzle.board));
+        }
+    }
+
+    let duplicates = find_duplicates(&boards, input.max_states);
+    match input.format {
+        Format::Json => println!(
+            "{}", serde_json::to_string_pretty(&duplicates).expect("Serializing duplicates failed.")
+        ),
+        Format::Text => {
+            for group in &duplicates.groups {
+                let likeness = match group.likeness {
+                    Likeness::Identical => "Identical",
+                    Likeness::Isomorphic => "Isomorphic",
+                    Likeness::Cluster => "Same cluster",
+                };
+                println!("{likeness}: {}", group.boards.join(", "));
+            }
+            if duplicates.groups.is_empty() {
+                println!("No duplicates found among {} puzzles.", boards.len());
+            }
+            if !duplicates.unexplored.is_empty() {
+                println!(
+                    "Clusters too large to search, see --max-states: {}", 
+                    duplicates.unexplored.join(", ")
+                );
+            }
+        }
+    }
+}
+
+
+/// Convert a gameboard file, as requested by the `convert` command. A csv 
+/// input board has the given `size`, or else the size in its file name.
+pub fn convert_board(input: &Convert, size: Option<(u8, u8)>) {
+    let contents = read_source(&input.input).expect("Reading board failed.");
+    let from = input.from.unwrap_or_else(|| BoardFormat::detect(&contents));
+    let Some(to) = input.to.or_else(|| BoardFormat::from_path(&input.output)) else {
+        panic!("Unknown format of {}, use --to.", input.output);
+    };
+
+    let board = convert::read(from, &input.input, &contents, size)
+        .unwrap_or_else(|error| panic!("{} is not a valid {from:?} board: {error}.", input.input));
+    let output = convert::write(to, &board)
+        .unwrap_or_else(|error| panic!("The board can't be written as {to:?}: {error}."));
+    if input.output == STDIN {
+        print!("{output}");
+        return;
+    }
+    fs::write(&input.output, output).expect("Writing board failed.");
+    println!("Converted {} ({from:?}) to {} ({to:?}).", input.input, input.output);
+}
+
+
+/// Write the SVG images requested by the `svg` command.
+pub fn export_svg(input: &Svg) {
+    let (name, board) = find_or_panic(&input.board_name);
+    let output = input.output.clone().unwrap_or(format!("results/{}.svg", result_file_name(&name)));
+
+    let Some(solution_file) = &input.solution else {
+        fs::write(&output, svg::board_svg(&board)).expect("Writing svg failed.");
+        println!("Written to {output}.");
+        return;
+    };
+
+    let contents = fs::read_to_string(solution_file).expect("Reading solution failed.");
+    let moves = Move::from_csv(&contents).expect("Invalid solution file.");
+
+    if input.frames {
+        let frames = svg::solution_frames(&board, &moves).expect("The solution contains an impossible move.");
+        let stem = output.strip_suffix(".svg").unwrap_or(&output);
+        for (number, frame) in frames.iter().enumerate() {
+            fs::write(format!("{stem}_{number:03}.svg"), frame).expect("Writing svg failed.");
+        }
+        println!("Written {} frames to {stem}_000.svg and onwards.", frames.len());
+    } else {
+        let strip = svg::solution_strip(&board, &moves, input.columns)
+            .expect("The solution contains an impossible move.");
+        fs::write(&output, strip).expect("Writing svg failed.");
+        println!("Written to {output}.");
+    }
+}
+
+
+/// Write the web page requested by the `html` command.
+pub fn export_html(input: &Html) {
+    let (name, board) = find_or_panic(&input.board_name);
+    let output = input.output.clone().unwrap_or(format!("results/{}.html", result_file_name(&name)));
+
+    let solution_csv = match &input.solution {
+        Some(solution_file) => fs::read_to_string(solution_file).expect("Reading solution failed."),
+        None => match Solver::from_board(board.clone(), usize::MAX).breadth_first() {
+            Ok(Some(solution)) => solution.history_csv(),
+            Ok(None) => panic!("The board has no solution."),
+            Err(error) => panic!("Solving failed, the board is invalid: {error}."),
+        },
+    };
+
+    let page = html::solution_html(&name, &board, &solution_csv).expect("The board has no exit.");
+    fs::write(&output, page).expect("Writing html failed.");
+    println!("Written to {output}.");
+}
+
+
+/// Solve the puzzles of a database and compare the solutions to the optimal
+/// lengths listed in the database.
+pub fn solve_database(input: &Database) {
+    let contents = read_source(&input.file).expect("Reading database failed.");
+    let entries = notation::read_database(&contents)
+        .skip(input.skip)
+        .take(input.limit.unwrap_or(usize::MAX))
+        .collect::<Vec<_>>();
+
+    let start_time = Instant::now();
+    let (mut solved, mut mismatched, mut skipped) = (0, 0, 0);
+
+    for entry in entries.iter().progress() {
+        // lines and puzzles that can't be read
+        let Some((entry, board)) = entry.as_ref().ok()
+            .and_then(|entry| Some((entry, notation::parse(&entry.notation).ok()?)))
+        else {
+            skipped += 1;
+            continue;
+        };
+
+        let mut solver = Solver::from_board(board, usize::MAX);
+        solver.set_show_progress(false);
+        let length = solver.breadth_first().ok().flatten().map(|solution| solution.history().len());
+
+        if length == Some(entry.moves) {
+            solved += 1;
+        } else {
+            mismatched += 1;
+            println!(
+                "{} (cluster of {} states): expected {} moves, found {length:?}.", 
+                entry.notation, entry.cluster_size, entry.moves
+            );
+        }
+    }
+
+    println!(
+        "Solved {solved} puzzles optimally, {mismatched} did not match and {skipped} were skipped, in {:.2?}.",
+        start_time.elapsed()
+    );
+}
+
+
+/// Print the result of a manually played game and exit. Prints nothing if
+/// the player quit the game.
+fn report_score(result: Result<Option<Score>, Error>) {
+    match result {
+        Err(error) => panic!("Aaaaaaa! {error}."),
+        Ok(None) => exit(0),
+        Ok(Some(score)) => {
+            println!(
+                "Final score: {} moves ({} steps), par is {} moves ({:+})!", 
+                score.moves, 
+                score.steps, 
+                score.par, 
+                score.over_par()
+            );
+            exit(0)
+        }
+    };
+}
@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use regex::Regex;
+
+use crate::board::Board;
+use crate::error::Error;
+use crate::notation;
+
+
+/// The formats a single gameboard can be read from and written to.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardFormat {
+    /// The gameboard csv format, with a vehicle and its coordinates per line.
+    Csv,
+    /// A text grid with a character or name per tile, see `Board::from_str`.
+    Grid,
+    /// The one-line notation of puzzle databases like rush.txt.
+    Notation,
+    /// The JSON form of a `Board`, as written by `--format json`.
+    Json,
+}
+
+
+impl BoardFormat {
+    /// Guess the format of a file from its extension. Text files can hold
+    /// either a grid or a one-line notation, and are assumed to be grids.
+    pub fn from_path(path: &str) -> Option<BoardFormat> {
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension {
+            "csv" => Some(BoardFormat::Csv),
+            "txt" => Some(BoardFormat::Grid),
+            "json" => Some(BoardFormat::Json),
+            _ => None,
+        }
+    }
+
+
+    /// Guess the format of a gameboard from its `contents`.
+    pub fn detect(contents: &str) -> BoardFormat {
+        let contents = contents.trim();
+        if contents.starts_with("car,") {
+            BoardFormat::Csv
+        } else if contents.starts_with('{') {
+            BoardFormat::Json
+        } else if !contents.contains(char::is_whitespace) {
+            BoardFormat::Notation
+        } else {
+            BoardFormat::Grid
+        }
+    }
+}
+
+
+/// Returns the width and height of a board from the "WIDTHxHEIGHT" part of
+/// its `name`, eg 6 columns and 7 rows for "Rushhour6x7_1.csv". Only the file
+/// name is searched, not the directories of a path.
+pub fn dimensions_from_name(name: &str) -> Option<(u8, u8)> {
+    let re = Regex::new(r"(\d+)x(\d+)").unwrap();
+    let file_name = Path::new(name).file_name()?.to_str()?;
+    let captures = re.captures(file_name)?;
+    Some((captures[1].parse().ok()?, captures[2].parse().ok()?))
+}
+
+
+/// Parses the size of a board given on the command line, either "6" for a
+/// square board or "6x7" for a board of 6 columns and 7 rows.
+pub fn parse_dimensions(text: &str) -> Result<(u8, u8), String> {
+    if let Ok(size) = text.parse() {
+        return Ok((size, size));
+    }
+    text.split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .ok_or(format!("{text} is not a size like 6 or 6x7"))
+}
+
+
+/// Parse a gameboard in the given `format`. The csv format does not store
+/// the size of the board, which is given by `size` as width and height or 
+/// else taken from the `name` of the file, see `dimensions_from_name`.
+///
+/// Returns an error if the `contents` are not a valid gameboard.
+pub fn read(format: BoardFormat, name: &str, contents: &str, size: Option<(u8, u8)>) -> Result<Board, Error> {
+    match format {
+        BoardFormat::Csv => {
+            let (width, height) = size.or_else(|| dimensions_from_name(name))
+                .ok_or_else(|| Error::Parse(format!("the size of {name}, which is not in its name")))?;
+            let mut board = Board::with_size(width, height);
+            board.fill_from_str(contents.trim_start())?;
+            Ok(board)
+        }
+        BoardFormat::Grid => contents.parse(),
+        BoardFormat::Notation => notation::parse(contents),
+        BoardFormat::Json => serde_json::from_str(contents)
+            .map_err(|error| Error::Parse(format!("JSON board {name}: {error}"))),
+    }
+}
+
+
+/// Write a gameboard in the given `format`.
+///
+/// Returns an error if the board can't be written in the one-line notation,
+/// see `notation::serialize`.
+pub fn write(format: BoardFormat, board: &Board) -> Result<String, Error> {
+    match format {
+        BoardFormat::Csv => Ok(board.to_csv()),
+        BoardFormat::Grid => Ok(board.to_string()),
+        BoardFormat::Notation => notation::serialize(board).map(|notation| notation + "\n"),
+        BoardFormat::Json => serde_json::to_string_pretty(board)
+            .map(|json| json + "\n")
+            .map_err(|error| Error::Unsupported(error.to_string())),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+
+    #[test]
+    fn dimensions_come_from_the_file_name_only() {
+        assert_eq!(dimensions_from_name("Rushhour6x7_1.csv"), Some((6, 7)));
+        assert_eq!(dimensions_from_name("/tmp/set2x3/Rushhour6x6_1.csv"), Some((6, 6)));
+        assert_eq!(dimensions_from_name("/tmp/set2x3/board.csv"), None);
+    }
+
+
+    #[test]
+    fn every_format_round_trips() {
+        let contents = include_str!("../gameboards/Rushhour6x6_1.csv");
+        let board = read(BoardFormat::Csv, "Rushhour6x6_1.csv", contents, None).unwrap();
+
+        for format in [BoardFormat::Csv, BoardFormat::Grid, BoardFormat::Notation, BoardFormat::Json] {
+            let written = write(format, &board).unwrap();
+            assert_eq!(BoardFormat::detect(&written), format);
+            let read = read(format, "board.txt", &written, Some((6, 6))).unwrap();
+            assert_eq!(read.to_string(), board.to_string(), "{format:?} changed the board");
+        }
+    }
+
+
+    #[test]
+    fn invalid_csv_is_an_error() {
+        let contents = "car,orientation,col,row,length\nA,Q,1,1,2\n";
+        assert!(read(BoardFormat::Csv, "Rushhour6x6_1.csv", contents, None).is_err());
+        // the size can't be taken from stdin
+        let contents = include_str!("../gameboards/Rushhour6x6_1.csv");
+        assert!(read(BoardFormat::Csv, "-", contents, None).is_err());
+    }
+}
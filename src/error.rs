@@ -0,0 +1,53 @@
+use std::fmt;
+
+use crate::board::Move;
+
+
+/// Everything that can go wrong reading, changing, solving and drawing
+/// boards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A file that could not be read or written, with the reason.
+    Io(String),
+    /// Text that is not in the expected format, like a line of a gameboard
+    /// file, a solution or a puzzle collection.
+    Parse(String),
+    /// A vehicle name that can't be used, see `VehicleId::new`.
+    InvalidName(String),
+    /// A vehicle that is not on the board.
+    NoVehicle(String),
+    /// A vehicle, wall or exit that does not fit on the board, or overlaps
+    /// another vehicle or wall.
+    DoesNotFit(String),
+    /// The target vehicle can't drive out of the exit, see `Board.is_won`.
+    InvalidGoal,
+    /// A goal of a `Solver` that no moves can reach, see `Goal.check`.
+    UnreachableGoal,
+    /// A move that is not one of the possible moves of the board.
+    InvalidMove(Move),
+    /// A board on which no vehicle can move.
+    NoMoves,
+    /// A board that can't be written in a format, like the one-line notation.
+    Unsupported(String),
+}
+
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(reason) => write!(f, "{reason}"),
+            Error::Parse(what) => write!(f, "could not read {what}"),
+            Error::InvalidName(name) => write!(f, "{name:?} is not a valid vehicle name"),
+            Error::NoVehicle(name) => write!(f, "there is no vehicle {name} on the board"),
+            Error::DoesNotFit(what) => write!(f, "{what} does not fit on the board"),
+            Error::InvalidGoal => write!(f, "the target vehicle can't drive out of the exit"),
+            Error::UnreachableGoal => write!(f, "the goal has other pieces than the board, so it can't be reached"),
+            Error::InvalidMove(veh_move) => write!(f, "{veh_move} is not a possible move"),
+            Error::NoMoves => write!(f, "no vehicle can move"),
+            Error::Unsupported(reason) => write!(f, "can't write the board, {reason}"),
+        }
+    }
+}
+
+
+impl std::error::Error for Error {}
@@ -0,0 +1,208 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::svg::{escape_xml, vehicle_colors};
+use crate::board::Board;
+use crate::error::Error;
+
+
+/// Page layout, styling and the script stepping through the solution. The
+/// placeholders in capitals are filled in by `solution_html`.
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>RushHour: TITLE</title>
+<style>
+  body { font-family: sans-serif; display: flex; flex-direction: column; align-items: center; }
+  #board { position: relative; background: #eeeeee; border: 3px solid #333333; margin: 1em 2em; }
+  .vehicle { position: absolute; box-sizing: border-box; border: 1px solid #333333; border-radius: 8px;
+             display: flex; align-items: center; justify-content: center; font-weight: bold; font-size: 24px;
+             transition: left 0.3s, top 0.3s; }
+  .vehicle.moved { border: 4px solid #000000; }
+  .wall { position: absolute; background: #555555; }
+  #exit { position: absolute; width: 0; height: 0; border: 10px solid transparent; }
+  #controls button { font-size: 1.2em; margin: 0 0.2em; }
+  #status { margin: 0.5em; min-height: 1.5em; }
+</style>
+</head>
+<body>
+<h1>TITLE</h1>
+<div id="board"><div id="exit"></div></div>
+<div id="status"></div>
+<div id="controls">
+  <button id="first">&#x23EE;</button>
+  <button id="previous">&#x23F4;</button>
+  <button id="play">&#x23F5;</button>
+  <button id="next">&#x23F5;&#x23F5;</button>
+  <button id="last">&#x23ED;</button>
+</div>
+<script>
+const WIDTH = BOARD_WIDTH;
+const HEIGHT = BOARD_HEIGHT;
+const TILE = 60;
+const COLORS = COLOR_TABLE;
+const EXIT = { side: "EXIT_SIDE", position: EXIT_POSITION };
+// [row, col] of every wall
+const WALLS = WALL_LIST;
+// the gameboard and the solution in their csv formats
+const boardCsv = BOARD_CSV;
+const solutionCsv = SOLUTION_CSV;
+
+// skip the heading, empty lines and directives of a csv file
+function readCsv(text) {
+  return text.trim().split("\n").slice(1)
+    .filter(line => line.trim() !== "" && !line.startsWith("#"))
+    .map(line => line.trim().split(","));
+}
+
+// free pieces ("F") are given as a horizontal run of tiles per row
+const vehicles = readCsv(boardCsv).map(([id, orientation, col, row, length]) => ({
+  id, horizontal: orientation !== "V", col: col - 1, row: row - 1, length: Number(length),
+}));
+const moves = readCsv(solutionCsv).map(([id, distance, axis]) => ({ id, distance: Number(distance), axis }));
+
+// positions of every vehicle after every move, starting with the board itself
+const states = [vehicles.map(v => ({ col: v.col, row: v.row }))];
+for (const move of moves) {
+  const next = states[states.length - 1].map(p => ({ ...p }));
+  vehicles.forEach((v, index) => {
+    if (v.id !== move.id) { return; }
+    if (move.axis ? move.axis === "H" : v.horizontal) { next[index].col += move.distance; } else { next[index].row += move.distance; }
+  });
+  states.push(next);
+}
+
+const board = document.getElementById("board");
+board.style.width = WIDTH * TILE + "px";
+board.style.height = HEIGHT * TILE + "px";
+for (const [row, col] of WALLS) {
+  const wall = document.createElement("div");
+  wall.className = "wall";
+  wall.style.left = col * TILE + "px";
+  wall.style.top = row * TILE + "px";
+  wall.style.width = wall.style.height = TILE + "px";
+  board.appendChild(wall);
+}
+const elements = vehicles.map((v, index) => {
+  const element = document.createElement("div");
+  const [fill, label] = COLORS[v.id];
+  element.className = "vehicle";
+  // label free pieces on their top row only
+  element.textContent = vehicles.findIndex(o => o.id === v.id) === index ? v.id : "";
+  element.style.background = fill;
+  element.style.color = label;
+  element.style.width = (v.horizontal ? v.length : 1) * TILE - 10 + "px";
+  element.style.height = (v.horizontal ? 1 : v.length) * TILE - 10 + "px";
+  board.appendChild(element);
+  return element;
+});
+
+// a red arrow pointing out of the board at the exit
+const exit = document.getElementById("exit");
+const opposite = { left: "right", right: "left", top: "bottom", bottom: "top" }[EXIT.side];
+exit.style["border-" + opposite] = "14px solid #d62728";
+exit.style["border-" + EXIT.side] = "none";
+if (EXIT.side === "left" || EXIT.side === "right") {
+  exit.style.left = (EXIT.side === "right" ? WIDTH * TILE + 4 : -18) + "px";
+  exit.style.top = EXIT.position * TILE + TILE / 2 - 10 + "px";
+} else {
+  exit.style.top = (EXIT.side === "bottom" ? HEIGHT * TILE + 4 : -18) + "px";
+  exit.style.left = EXIT.position * TILE + TILE / 2 - 10 + "px";
+}
+
+let current = 0;
+let timer = null;
+
+function show(step) {
+  current = Math.max(0, Math.min(step, moves.length));
+  states[current].forEach((position, index) => {
+    elements[index].style.left = position.col * TILE + 5 + "px";
+    elements[index].style.top = position.row * TILE + 5 + "px";
+    elements[index].classList.toggle("moved", current > 0 && vehicles[index].id === moves[current - 1].id);
+  });
+  const move = moves[current - 1];
+  document.getElementById("status").textContent = current === 0
+    ? `Start, ${moves.length} moves to go`
+    : `Move ${current}/${moves.length}: ${move.id} by ${move.distance}${{ H: " horizontally", V: " vertically" }[move.axis] ?? ""}`;
+}
+
+function stop() {
+  clearInterval(timer);
+  timer = null;
+  document.getElementById("play").innerHTML = "&#x23F5;";
+}
+
+document.getElementById("first").onclick = () => { stop(); show(0); };
+document.getElementById("previous").onclick = () => { stop(); show(current - 1); };
+document.getElementById("next").onclick = () => { stop(); show(current + 1); };
+document.getElementById("last").onclick = () => { stop(); show(moves.length); };
+document.getElementById("play").onclick = () => {
+  if (timer !== null) { stop(); return; }
+  if (current === moves.length) { show(0); }
+  document.getElementById("play").innerHTML = "&#x23F8;";
+  timer = setInterval(() => { show(current + 1); if (current === moves.length) { stop(); } }, 700);
+};
+
+show(0);
+</script>
+</body>
+</html>
+"##;
+
+
+/// Returns `value` as JSON to embed in a script element. A "<" could end 
+/// the script element, so it is escaped.
+fn script_json<T: Serialize + ?Sized>(value: &T) -> String {
+    serde_json::to_string(value)
+        .expect("Serializing failed.")
+        .replace('<', "\\u003c")
+}
+
+
+/// Replaces every placeholder in `template` by its value in a single pass,
+/// so a value holding the name of another placeholder is left as it is.
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some((start, placeholder, value)) = values.iter()
+        .filter_map(|(placeholder, value)| Some((rest.find(placeholder)?, placeholder, value)))
+        .min_by_key(|(start, _, _)| *start)
+    {
+        page.push_str(&rest[..start]);
+        page.push_str(value);
+        rest = &rest[start + placeholder.len()..];
+    }
+    page.push_str(rest);
+    page
+}
+
+
+/// Creates a single HTML page showing a solution of a gameboard, which can be
+/// opened offline in any browser.
+///
+/// The `board` is embedded in the page in the gameboard file format written by
+/// `Board.to_csv`, and `solution_csv` holds the moves in the format written by
+/// `Board.export`. Both are embedded as JavaScript strings, along with the
+/// colors of the vehicles, the walls and the exit. The `title` is escaped.
+/// 
+/// Returns an error if the board has no exit, see `Board.exit`.
+pub fn solution_html(title: &str, board: &Board, solution_csv: &str) -> Result<String, Error> {
+    let exit = board.exit()?;
+    let color_table = board.vehicles().into_iter()
+        .map(|vehicle| (vehicle.id_string(), vehicle_colors(&vehicle, board.target())))
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(fill_template(TEMPLATE, &[
+        ("TITLE", escape_xml(title)),
+        ("EXIT_SIDE", exit.side.to_str().to_string()),
+        ("EXIT_POSITION", exit.position.to_string()),
+        ("WALL_LIST", script_json(&board.walls())),
+        ("BOARD_WIDTH", board.width().to_string()),
+        ("BOARD_HEIGHT", board.height().to_string()),
+        ("COLOR_TABLE", script_json(&color_table)),
+        ("BOARD_CSV", script_json(board.to_csv().trim())),
+        ("SOLUTION_CSV", script_json(solution_csv.trim())),
+    ]))
+}
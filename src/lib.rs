@@ -0,0 +1,39 @@
+//! Solving, playing and drawing RushHour puzzles.
+//!
+//! The `rusthour` binary is a command line interface on top of this library.
+//! Interactive play, the gameboards directory and the other settings of the
+//! command line live in the binary, not in the library.
+
+pub mod board;
+pub mod collection;
+pub mod convert;
+pub mod error;
+pub mod html;
+pub mod notation;
+pub mod solvers;
+pub mod svg;
+
+pub use board::{Board, Direction, Exit, Move, Side, VehicleId, VehiclePlacement};
+pub use error::Error;
+pub use solvers::{CostModel, Goal, Solution, Solver};
+
+
+/// Solve the gameboard in the csv file `filename` and export the solution to
+/// results/solution.csv.
+/// 
+/// Returns an error if the file is not a valid gameboard, see 
+/// `Solver::from_file`.
+pub fn breadth_first_solve(filename: &str) -> Result<(), Error> {
+    let solver = Solver::from_file(filename, usize::MAX)?;
+    if let Some(solution) = solver.breadth_first()? {
+        solution.export("results/solution.csv");
+    }
+    Ok(())
+}
+
+
+/// Solve a 9x9 gameboard, as done by the benchmark.
+pub fn bench_breadth_first() {
+    breadth_first_solve(concat!(env!("CARGO_MANIFEST_DIR"), "/gameboards/Rushhour9x9_4.csv"))
+        .expect("The benchmark gameboard is invalid.");
+}
@@ -1,61 +1,268 @@
-mod ui;
-// mod solvers;
+use std::env;
+use std::path::PathBuf;
+use rusthour::convert;
+use rusthour::board::set_colored_output;
+use rusthour::convert::BoardFormat;
+
+use clap::{Args, Parser, Subcommand};
 
-use std::process::exit;
-use ui::{play, list_boards, print_boards};
-use ui::solvers::Solver;
+mod commands;
+mod ui;
 
-use clap::{Args, Parser, Subcommand, ValueEnum};
+use commands::{collect_boards, convert_board, dedupe_boards, export_html, export_svg, play_manually, replay_solution, solve, solve_database, verify_solution};
+use ui::{parse_weight, print_boards, set_board_size, set_boards_dir, Cost, Format};
 
 
 #[derive(Parser)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Actions>,
+
+    /// Print gameboards without colors. Also set by the `NO_COLOR` 
+    /// environment variable.
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Directory searched for gameboards by name, by default ./gameboards.
+    /// Also set by the `RUSTHOUR_BOARDS_DIR` environment variable.
+    #[arg(long, global = true)]
+    boards_dir: Option<PathBuf>,
+
+    /// Size of a csv gameboard, like 6 or 6x7, if it is read from stdin or 
+    /// its file name does not contain it.
+    #[arg(long, global = true, value_parser = convert::parse_dimensions)]
+    size: Option<(u8, u8)>,
 }
 
 
 #[derive(Debug, Subcommand)]
 enum Actions {
     /// Print out all found gameboards
-    List,
-    /// Todo!
-    Solve,
+    List(List),
+    /// Find the shortest solution of a gameboard
+    Solve(Solve),
     /// Play RustHour manually
     Manual(Manual),
+    /// Step through a solution of a gameboard
+    Replay(Replay),
+    /// Draw a gameboard or a solution as SVG images
+    Svg(Svg),
+    /// Write a web page stepping through a solution
+    Html(Html),
+    /// Solve the puzzles of a database in one-line notation, like rush.txt
+    Database(Database),
+    /// Check that a solution solves a gameboard
+    Verify(Verify),
+    /// Bundle the gameboard files into a single puzzle collection
+    Collect(Collect),
+    /// Convert a gameboard file to another format
+    Convert(Convert),
+    /// Find duplicate puzzles among the gameboards and collections
+    Dedupe(Dedupe),
+}
+
+
+#[derive(Args, Debug)]
+struct List {
+    /// Output format.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+
+#[derive(Args, Debug)]
+struct Solve {
+    /// The name or path of the gameboard to solve, or "-" for stdin.
+    #[arg(required_unless_present = "all")]
+    board_name: Option<String>,
+    /// Solve every gameboard and collection puzzle. The optimal lengths are 
+    /// written back into the collections.
+    #[arg(long, conflicts_with = "board_name")]
+    all: bool,
+    /// Search for the moves leading to this board instead of moving the 
+    /// target vehicle to the exit, eg to find the distance between two 
+    /// states of a puzzle.
+    #[arg(long, conflicts_with = "all")]
+    goal: Option<String>,
+    /// Only the vehicles on the --goal board have to reach their positions,
+    /// the other vehicles may end up anywhere.
+    #[arg(long, requires = "goal")]
+    partial: bool,
+    /// How the moves of a solution are counted. Anything but slides 
+    /// searches for the cheapest solution instead of the shortest one.
+    #[arg(long, value_enum, default_value_t, conflicts_with = "all")]
+    cost: Cost,
+    /// Weight of a vehicle for --cost weighted, eg `--weight A=3`.
+    #[arg(long, value_parser = parse_weight)]
+    weight: Vec<(String, u64)>,
+    /// Search boards that only differ in the names of vehicles, or are 
+    /// mirrored from top to bottom keeping the exit row, only once. Speeds 
+    /// up puzzles with many alike pieces, like Klotski.
+    #[arg(long)]
+    canonical: bool,
+    /// Stop searching after this many moves, or at this cost with --cost.
+    #[arg(long, default_value_t = usize::MAX)]
+    max_depth: usize,
+    /// Output format.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+
+#[derive(Args, Debug)]
+struct Verify {
+    /// The name or path of the gameboard the solution belongs to, or "-" for stdin.
+    board_name: String,
+    /// The solution to check, as exported by `solve` or `manual`.
+    solution: String,
+    /// Output format.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
 }
 
 
 #[derive(Args, Debug)]
 struct Manual {
-    /// The name of the gameboard to solve manually.
+    /// The name or path of the gameboard to solve manually.
+    #[arg(required_unless_present = "resume")]
+    board_name: Option<String>,
+    /// Continue a game saved in this file, eg "results/6x6_1.save".
+    #[arg(long, conflicts_with = "board_name")]
+    resume: Option<String>,
+}
+
+
+#[derive(Args, Debug)]
+struct Replay {
+    /// The name or path of the gameboard the solution belongs to, or "-" for stdin.
     board_name: String,
+    /// The solution to show, as exported by `solve` or `manual`.
+    solution: String,
+    /// Milliseconds to wait between moves. Without a delay, every move
+    /// waits for the enter key.
+    #[arg(long)]
+    delay: Option<u64>,
+}
+
+
+#[derive(Args, Debug)]
+struct Svg {
+    /// The name or path of the gameboard to draw, or "-" for stdin.
+    board_name: String,
+    /// Draw every state of this solution instead of only the starting board.
+    #[arg(long)]
+    solution: Option<String>,
+    /// File to write to, by default "results/<board name>.svg".
+    #[arg(short, long)]
+    output: Option<String>,
+    /// Write every state of the solution to its own numbered file instead of
+    /// drawing them all in one image.
+    #[arg(long, requires = "solution")]
+    frames: bool,
+    /// Number of states drawn next to each other in a single image.
+    #[arg(long, default_value_t = 6)]
+    columns: usize,
+}
+
+
+#[derive(Args, Debug)]
+struct Html {
+    /// The name or path of the gameboard to show, or "-" for stdin.
+    board_name: String,
+    /// The solution to show, as exported by `solve` or `manual`. The board 
+    /// is solved first if no solution is given.
+    #[arg(long)]
+    solution: Option<String>,
+    /// File to write to, by default "results/<board name>.html".
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+
+#[derive(Args, Debug)]
+struct Collect {
+    /// The collection file to write, eg "gameboards/classic.json".
+    output: String,
+    /// Name of the collection, by default the name of the output file.
+    #[arg(long)]
+    name: Option<String>,
+    /// Leave out gameboards with the same canonical form as an earlier one,
+    /// like relabeled or mirrored copies.
+    #[arg(long)]
+    unique: bool,
+}
+
+
+#[derive(Args, Debug)]
+struct Convert {
+    /// The gameboard file to read, or "-" for stdin.
+    input: String,
+    /// The file to write, or "-" for stdout.
+    output: String,
+    /// Format of the input, detected from its contents by default.
+    #[arg(long, value_enum)]
+    from: Option<BoardFormat>,
+    /// Format of the output, by default taken from its extension.
+    #[arg(long, value_enum)]
+    to: Option<BoardFormat>,
+}
+
+
+#[derive(Args, Debug)]
+struct Database {
+    /// The database file, with an optimal length, a puzzle and a cluster size
+    /// on every line, or "-" for stdin.
+    file: String,
+    /// Only solve this many puzzles.
+    #[arg(long)]
+    limit: Option<usize>,
+    /// Skip this many puzzles at the start of the database.
+    #[arg(long, default_value_t = 0)]
+    skip: usize,
+}
+
+
+#[derive(Args, Debug)]
+struct Dedupe {
+    /// Largest number of boards reachable from a puzzle that are searched 
+    /// for other puzzles in the same cluster.
+    #[arg(long, default_value_t = 1_000_000)]
+    max_states: usize,
+    /// Output format.
+    #[arg(long, value_enum, default_value_t)]
+    format: Format,
 }
 
 
 fn main() {
     let cli = Cli::parse();
 
-    if let None = &cli.command {
+    if cli.command.is_none() {
         panic!("No commands supplied!");
     }
 
+    if cli.no_color || env::var_os("NO_COLOR").is_some() {
+        set_colored_output(false);
+    }
+    if let Some(dir) = cli.boards_dir.clone().or_else(|| env::var_os("RUSTHOUR_BOARDS_DIR").map(PathBuf::from)) {
+        set_boards_dir(dir);
+    }
+    if let Some((width, height)) = cli.size {
+        set_board_size(width, height);
+    }
+
     match &cli.command.unwrap() {
-        Actions::List => print_boards(),
-        Actions::Manual(input) => {
-            let board_list = match list_boards() {
-                Ok(result) => result,
-                Err(error) => {
-                    println!("{}", error);
-                    panic!("No valid dir found.");
-                }
-            };
-            
-            println!("Searching board {}", input.board_name);
-            // let re = Regex::new(r"\d+").unwrap();
-            // let Some(size) = re.captures(filename) else {panic!("Regex failed.")};
-        }
-        _ => {}
+        Actions::List(input) => print_boards(input.format),
+        Actions::Manual(input) => play_manually(input),
+        Actions::Solve(input) => solve(input),
+        Actions::Verify(input) => verify_solution(input),
+        Actions::Replay(input) => replay_solution(input),
+        Actions::Svg(input) => export_svg(input),
+        Actions::Html(input) => export_html(input),
+        Actions::Database(input) => solve_database(input),
+        Actions::Collect(input) => collect_boards(input),
+        Actions::Convert(input) => convert_board(input, cli.size),
+        Actions::Dedupe(input) => dedupe_boards(input),
     }
     // match cli.command {
     //     Actions::List => list_boards(),
@@ -77,24 +284,3 @@ fn main() {
     // breadth_first_solve("/home/wessel/Documents/rust_hour/rusthour/gameboards/Rushhour9x9_4.csv");
     // play_manually("/home/wessel/Documents/rust_hour/rusthour/gameboards/Rushhour6x6_test.csv");
 }
-
-pub fn breadth_first_solve(filename: &str) {
-    let solver = Solver::from_file(filename, usize::MAX);
-    let _ = solver.breadth_first();
-}
-
-
-pub fn bench_breadth_first() {
-    breadth_first_solve("/home/pluumenbrownie/rust_projects/rusthour/gameboards/Rushhour9x9_4.csv");
-}
-
-
-pub fn play_manually(filename: &str) {
-    match play(filename) {
-        Err(()) => panic!("Aaaaaaa!"),
-        Ok(score) => {
-            println!("Final score: {score}!");
-            exit(0)
-        }
-    };
-}
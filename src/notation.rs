@@ -0,0 +1,179 @@
+use crate::board::{Board, Direction};
+use crate::error::Error;
+
+
+/// Character of an empty tile in the one-line notation.
+const EMPTY: char = 'o';
+/// Character of a wall in the one-line notation.
+const WALL: char = 'x';
+/// Name of the target car in the one-line notation.
+const TARGET: char = 'A';
+/// Name of the target car on a `Board`.
+const BOARD_TARGET: char = 'X';
+
+
+/// A puzzle from a database like `rush.txt`, with its known optimal solution
+/// length.
+#[derive(Debug, Clone)]
+pub struct DatabaseEntry {
+    /// Number of moves of the shortest solution.
+    pub moves: usize,
+    /// The puzzle in one-line notation.
+    pub notation: String,
+    /// Number of states in the cluster of the puzzle.
+    pub cluster_size: usize,
+}
+
+
+/// The target car is called "A" in the one-line notation, but "X" on a
+/// `Board`. Swaps the two names, so a car called "X" in the notation is
+/// called "A" on the board and the other way around.
+const fn swap_target_name(name: char) -> char {
+    match name {
+        TARGET => BOARD_TARGET,
+        BOARD_TARGET => TARGET,
+        _ => name,
+    }
+}
+
+
+/// Parses a `Board` from the one-line notation used by Michael Fogleman's
+/// puzzle database: the tiles in row-major order, `o` (or `.`) for an empty
+/// tile, `x` for a wall and capital letters for vehicles, with `A` as the
+/// target car. A 6x6 puzzle is 36 characters long.
+///
+/// Returns an error if the notation is not a square board, or contains 
+/// vehicles shorter than two tiles or vehicles that are not straight.
+pub fn parse(notation: &str) -> Result<Board, Error> {
+    let notation = notation.trim();
+    let invalid = || Error::Parse(format!("notation {notation:?}"));
+    let tiles = notation.chars().collect::<Vec<_>>();
+    let size = (1..=u8::MAX)
+        .find(|size| usize::from(*size) * usize::from(*size) == tiles.len())
+        .ok_or_else(invalid)?;
+    let walls = tiles.iter()
+        .enumerate()
+        .filter(|(_, tile)| **tile == WALL)
+        .map(|(index, _)| (index / usize::from(size), index % usize::from(size)))
+        .collect::<Vec<_>>();
+
+    let tiles = tiles.into_iter()
+        .map(|tile| match tile {
+            EMPTY | '.' | WALL => Ok(None),
+            'A'..='Z' => Ok(Some(swap_target_name(tile).to_string())),
+            _ => Err(invalid()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut board = Board::from_tiles(size, size, tiles)?;
+    // the notation has no free pieces, which `Board::from_tiles` would make
+    // of the pieces that are not a straight bar
+    if board.vehicles().iter().any(|vehicle| vehicle.direction == Direction::Free) {
+        return Err(invalid());
+    }
+    for wall in &walls {
+        board.add_wall(wall)?;
+    }
+    Ok(board)
+}
+
+
+/// Writes a `Board` in the one-line notation read by `parse`. The "X" car
+/// becomes the target car "A", see `swap_target_name`.
+///
+/// Returns an error if the board is not square, has another goal than the 
+/// "X" car reaching its default exit, or contains free pieces, one-way 
+/// vehicles or vehicles with names other than a single capital letter.
+pub fn serialize(board: &Board) -> Result<String, Error> {
+    let size = board.height();
+    if board.width() != size {
+        return Err(Error::Unsupported(String::from("the one-line notation only has square boards")));
+    }
+    if !board.has_default_goal() {
+        return Err(Error::Unsupported(String::from("the one-line notation only has the X car leaving on the right")));
+    }
+    let mut tiles = vec![EMPTY; size * size];
+    for (row, col) in board.walls() {
+        tiles[row * size + col] = WALL;
+    }
+
+    for vehicle in board.vehicles() {
+        if vehicle.one_way.is_some() || vehicle.direction == Direction::Free {
+            return Err(Error::Unsupported(String::from("the one-line notation has no free pieces or one-way vehicles")));
+        }
+        let id = vehicle.id_string();
+        let mut name = id.chars();
+        let (Some(name @ 'A'..='Z'), None) = (name.next(), name.next()) else {
+            return Err(Error::Unsupported(format!("the one-line notation has no vehicle names like {id}")));
+        };
+
+        for offset in 0..usize::from(vehicle.length) {
+            let (row, col) = match vehicle.direction {
+                Direction::Horizontal => (vehicle.row, vehicle.col + offset),
+                _ => (vehicle.row + offset, vehicle.col),
+            };
+            tiles[row * size + col] = swap_target_name(name);
+        }
+    }
+    Ok(tiles.into_iter().collect())
+}
+
+
+/// Reads the puzzles of a database in the format of `rush.txt`: every line
+/// holds the optimal number of moves, the puzzle in one-line notation and
+/// the size of its cluster, separated by spaces.
+///
+/// Lines that don't match this format are returned as errors, so a single
+/// bad line does not stop reading the rest of a large database.
+pub fn read_database(contents: &str) -> impl Iterator<Item = Result<DatabaseEntry, Error>> + '_ {
+    contents.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let invalid = || Error::Parse(format!("database line {line:?}"));
+            let mut fields = line.split_whitespace();
+            let moves = fields.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+            let notation = fields.next().ok_or_else(invalid)?.to_string();
+            let cluster_size = fields.next().ok_or_else(invalid)?.parse().map_err(|_| invalid())?;
+
+            Ok(DatabaseEntry { moves, notation, cluster_size })
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A puzzle of the database, with a wall.
+    const PUZZLE: &str = "IBBxooIooLDDJAALooJoKEEMFFKooMGGHHHM";
+
+
+    #[test]
+    fn notation_round_trip() {
+        let board = parse(PUZZLE).unwrap();
+        assert_eq!(board.walls(), vec![(0, 3)]);
+        assert_eq!(serialize(&board), Ok(PUZZLE.to_string()));
+    }
+
+
+    #[test]
+    fn target_car_is_swapped() {
+        let board = parse(PUZZLE).unwrap();
+        assert_eq!(board.to_string().lines().nth(2), Some("JXXL.."));
+    }
+
+
+    #[test]
+    fn only_straight_vehicles_are_read() {
+        // a single tile, and a piece bent around a corner
+        assert!(parse("AAoooooooooooooBoooooooooooooooooooo").is_err());
+        assert!(parse("AAooooooooooBBoooooBoooooooooooooooo").is_err());
+    }
+
+
+    #[test]
+    fn only_capital_letters_are_written() {
+        let board: Board = "XX.\nb..\nb..\n".parse().unwrap();
+        assert!(serialize(&board).is_err());
+    }
+}
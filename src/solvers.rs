@@ -1,66 +1,287 @@
 
-// #[path ="board.rs"]
-pub mod board;
-use std::{collections::HashSet, mem};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::mem;
 
-use board::Board;
-use indicatif::{ProgressIterator, ProgressStyle};
-use regex::Regex;
+use crate::board::{Board, Direction, Move, VehicleId, VehiclePlacement};
+use crate::convert::dimensions_from_name;
+use crate::error::Error;
+use indicatif::{ProgressBar, ProgressIterator, ProgressStyle};
+use serde::{Deserialize, Serialize};
+
+
+/// Outcome of solving a gameboard, as reported by the `solve` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Solution {
+    /// Name of the solved gameboard.
+    pub board: String,
+    /// Moves of the shortest solution, or `None` if no solution was found.
+    pub moves: Option<Vec<Move>>,
+    /// Time spent solving, in seconds.
+    pub seconds: f64,
+    /// Total cost of the moves, if the solution was searched for with a
+    /// `CostModel` other than `CostModel::Slides`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost: Option<u64>,
+}
+
+
+/// The boards a `Solver` searches for.
+#[derive(Debug, Clone, Default)]
+pub enum Goal {
+    /// The target vehicle is in front of the exit, see `Board.is_won`.
+    #[default]
+    Exit,
+    /// Every vehicle is where it is on the given board.
+    Board(Box<Board>),
+    /// The given vehicles are at the given positions, the other vehicles may
+    /// be anywhere.
+    Vehicles(Vec<VehiclePlacement>),
+}
+
+
+impl Goal {
+    /// Returns a `Result` containing `true` if `board` is one of the boards 
+    /// searched for.
+    /// 
+    /// An `Error` can be returned for the `Goal::Exit` of an invalid board,
+    /// see `Board.is_won`.
+    pub fn is_reached(&self, board: &Board) -> Result<bool, Error> {
+        match self {
+            Goal::Exit => board.is_won(),
+            Goal::Board(target) => Ok(board.contents == target.contents),
+            Goal::Vehicles(vehicles) => Ok(vehicles.iter().all(|vehicle| board.has_vehicle(vehicle))),
+        }
+    }
+
+
+    /// Returns an `Error` if the goal can never be reached from `board`, as
+    /// the goal board has other pieces, walls or size, or a vehicle of the
+    /// partial goal is not on the board with the same direction and length.
+    pub fn check(&self, board: &Board) -> Result<(), Error> {
+        let reachable = match self {
+            Goal::Exit => true,
+            Goal::Board(target) => board.has_same_pieces(target),
+            Goal::Vehicles(vehicles) => {
+                let on_board = board.vehicles();
+                vehicles.iter().all(|vehicle| on_board.iter().any(|placed|
+                    placed.id == vehicle.id
+                        && placed.direction == vehicle.direction
+                        // free pieces are placed as runs of any length
+                        && (vehicle.direction == Direction::Free || placed.length == vehicle.length)
+                ))
+            }
+        };
+        if reachable { Ok(()) } else { Err(Error::UnreachableGoal) }
+    }
+}
+
+
+/// How the cost of a solution is counted, see `Solver.uniform_cost`.
+#[derive(Debug, Clone, Default)]
+pub enum CostModel {
+    /// Every move costs 1, however many tiles the vehicle slides.
+    #[default]
+    Slides,
+    /// Every tile a vehicle slides over costs 1, as in competitions scoring
+    /// by steps.
+    Steps,
+    /// Every tile a vehicle slides over costs the weight of the vehicle, eg
+    /// to make trucks more expensive to move. Vehicles without a weight 
+    /// cost 1 per tile.
+    Weighted(HashMap<VehicleId, u64>),
+}
+
+
+impl CostModel {
+    /// Returns the cost of a single move.
+    pub fn cost(&self, veh_move: &Move) -> u64 {
+        let steps = u64::from(veh_move.direction.unsigned_abs());
+        match self {
+            CostModel::Slides => 1,
+            CostModel::Steps => steps,
+            CostModel::Weighted(weights) => steps * weights.get(&veh_move.vehicle_id).copied().unwrap_or(1),
+        }
+    }
+
+
+    /// Returns the summed cost of the `moves`.
+    pub fn total(&self, moves: &[Move]) -> u64 {
+        moves.iter().map(|veh_move| self.cost(veh_move)).sum()
+    }
+}
+
+
+/// A board waiting to be expanded by `Solver.uniform_cost`, ordered so the
+/// `BinaryHeap` pops the cheapest board first.
+struct QueuedBoard {
+    cost: u64,
+    board: Board,
+}
+
+
+impl PartialEq for QueuedBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+
+impl Eq for QueuedBoard {}
+
+
+impl PartialOrd for QueuedBoard {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+
+impl Ord for QueuedBoard {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Reverse(self.cost).cmp(&Reverse(other.cost))
+    }
+}
 
 
 pub struct Solver {
     board: Board,
     max_depth: usize,
+    show_progress: bool,
+    goal: Goal,
+    cost_model: CostModel,
+    canonical: bool,
 }
 
 impl Solver {
-    pub fn from_file(filename: &str, max_depth: usize) -> Solver {
-        let re = Regex::new(r"\d+").unwrap();
-        let Some(size) = re.captures(filename) else {panic!("Regex failed.")};
-        let mut board = Board::new(size[0].parse::<u8>().expect("Parsing failed."));
+    /// Create a `Solver` starting from the csv gameboard file `filename`, 
+    /// with the size of the board in its name, see `dimensions_from_name`.
+    /// 
+    /// Returns an error if the name holds no size, or the file can't be read
+    /// or is not a valid gameboard.
+    pub fn from_file(filename: &str, max_depth: usize) -> Result<Solver, Error> {
+        let (width, height) = dimensions_from_name(filename)
+            .ok_or_else(|| Error::Parse(format!("the size of {filename}, which is not in its name")))?;
+        let mut board = Board::with_size(width, height);
 
-        board.fill(filename);
+        board.fill(filename)?;
+        Ok(Solver::from_board(board, max_depth))
+    }
+
+
+    /// Create a `Solver` starting from the given `board`. Moves already 
+    /// made on the `board` are kept in the history of the solution.
+    pub fn from_board(board: Board, max_depth: usize) -> Solver {
         Solver {
             board, 
-            max_depth
+            max_depth,
+            show_progress: true,
+            goal: Goal::Exit,
+            cost_model: CostModel::Slides,
+            canonical: false,
         }
     }
 
 
-    fn set_max_depth(&mut self, new_depth: usize) {
+    pub fn set_max_depth(&mut self, new_depth: usize) {
         self.max_depth = new_depth;
     }
 
 
-    pub fn breadth_first(&self) -> Result<(), ()> {
+    /// Search for the boards matching `goal` instead of boards where the 
+    /// target vehicle reaches the exit.
+    pub fn set_goal(&mut self, goal: Goal) {
+        self.goal = goal;
+    }
+
+
+    /// Count the cost of solutions found by `Solver.uniform_cost` with
+    /// `cost_model` instead of counting moves.
+    pub fn set_cost_model(&mut self, cost_model: CostModel) {
+        self.cost_model = cost_model;
+    }
+
+
+    /// Treat boards with the same canonical form as the same state, see 
+    /// `Board.canonical`, so relabeled and mirrored copies of a board are 
+    /// only searched once. 
+    /// 
+    /// Ignored for goals other than `Goal::Exit` and for 
+    /// `CostModel::Weighted`, which depend on the names of the vehicles.
+    pub fn set_canonical(&mut self, canonical: bool) {
+        self.canonical = canonical;
+    }
+
+
+    /// Returns the hash identifying the state of `board` in the search, see 
+    /// `Solver.set_canonical`.
+    fn state_hash(&self, board: &Board) -> u64 {
+        let by_name = !matches!(self.goal, Goal::Exit) || matches!(self.cost_model, CostModel::Weighted(_));
+        if self.canonical && !by_name {
+            board.canonical_hash()
+        } else {
+            board.get_hash()
+        }
+    }
+
+
+    /// Show or hide the progress bars drawn while solving.
+    pub fn set_show_progress(&mut self, show_progress: bool) {
+        self.show_progress = show_progress;
+    }
+
+
+    /// Searches for the shortest solution, one generation of boards at a time.
+    /// A solution leads to a board matching the goal, see `Solver.set_goal`.
+    /// 
+    /// Returns the solved `Board`, of which the history contains the solution,
+    /// or `None` when no solution exists within `max_depth` moves.
+    /// An `Error` is returned for a goal that can never be reached, see
+    /// `Goal.check`.
+    pub fn breadth_first(&self) -> Result<Option<Board>, Error> {
+        self.goal.check(&self.board)?;
         let board = self.board.clone();
+        if self.goal.is_reached(&board)? {
+            return Ok(Some(board));
+        }
+
         let mut depth_counter = 0usize;
         let mut archive = HashSet::new();
-        archive.insert(board.get_hash());
+        archive.insert(self.state_hash(&board));
         let mut current_generation: Vec<Board> = vec![board];
         let mut next_generation: Vec<Board> = vec![];
 
         while depth_counter < self.max_depth {
+            // every reachable board has been visited
+            if current_generation.is_empty() {
+                return Ok(None);
+            }
             next_generation.clear();
 
+            let progress_bar = if self.show_progress {
+                ProgressBar::new(current_generation.len() as u64)
+                    .with_style(ProgressStyle::with_template(
+                        "{msg} [{elapsed_precise}] {wide_bar} {pos}/{len} "
+                    ).expect("template failed."))
+            } else {
+                ProgressBar::hidden()
+            };
             let iterator_with_progress_bar = current_generation.iter()
-                .progress_with_style(ProgressStyle::with_template(
-                    "{msg} [{elapsed_precise}] {wide_bar} {pos}/{len} "
-                ).expect("template failed."))
+                .progress_with(progress_bar)
                 .with_message(format!("Depth: {}", depth_counter)
             );
 
             for state in iterator_with_progress_bar {
-                let possible_moves = state.possible_moves()?;
+                // one-way vehicles can leave a board without any moves
+                let possible_moves = state.possible_moves().unwrap_or_default();
 
                 for vehicle_move in possible_moves {
                     let mut new_state = state.clone();
                     new_state.move_vehicle(vehicle_move);
-                    if new_state.is_won()? {
-                        new_state.export("results/solution.csv");
-                        return Ok(());
+                    if self.goal.is_reached(&new_state)? {
+                        return Ok(Some(new_state));
                     }
-                    if archive.insert(new_state.get_hash()) {
+                    if archive.insert(self.state_hash(&new_state)) {
                         next_generation.push(new_state);
                     }
                 }
@@ -70,6 +291,246 @@ impl Solver {
             depth_counter += 1;
         }
 
-        Ok(())
+        Ok(None)
+    }
+
+
+    /// Searches for the cheapest solution under the cost model, see 
+    /// `Solver.set_cost_model`, always expanding the cheapest board found
+    /// so far (Dijkstra's algorithm). With `CostModel::Slides` this finds
+    /// a solution as short as `Solver.breadth_first`, only slower.
+    /// 
+    /// Returns the solved `Board`, of which the history contains the solution,
+    /// or `None` when no solution exists costing at most `max_depth`.
+    /// An `Error` is returned for a goal that can never be reached, see
+    /// `Goal.check`.
+    pub fn uniform_cost(&self) -> Result<Option<Board>, Error> {
+        self.goal.check(&self.board)?;
+        let max_cost = u64::try_from(self.max_depth).unwrap_or(u64::MAX);
+        let mut lowest_costs = HashMap::new();
+        lowest_costs.insert(self.state_hash(&self.board), 0);
+        let mut queue = BinaryHeap::new();
+        queue.push(QueuedBoard { cost: 0, board: self.board.clone() });
+
+        let progress_bar = if self.show_progress {
+            ProgressBar::new_spinner()
+                .with_style(ProgressStyle::with_template(
+                    "{msg} [{elapsed_precise}] {spinner} {pos} boards"
+                ).expect("template failed."))
+        } else {
+            ProgressBar::hidden()
+        };
+
+        while let Some(QueuedBoard { cost, board }) = queue.pop() {
+            // a cheaper way to this board was queued later and expanded already
+            if lowest_costs.get(&self.state_hash(&board)).is_some_and(|&lowest| lowest < cost) {
+                continue;
+            }
+            if self.goal.is_reached(&board)? {
+                progress_bar.finish_and_clear();
+                return Ok(Some(board));
+            }
+            progress_bar.set_message(format!("Cost: {}", cost));
+            progress_bar.inc(1);
+
+            // one-way vehicles can leave a board without any moves
+            for vehicle_move in board.possible_moves().unwrap_or_default() {
+                let new_cost = cost + self.cost_model.cost(&vehicle_move);
+                if new_cost > max_cost {
+                    continue;
+                }
+                let mut new_state = board.clone();
+                new_state.move_vehicle(vehicle_move);
+
+                let lowest = lowest_costs.entry(self.state_hash(&new_state)).or_insert(u64::MAX);
+                if new_cost < *lowest {
+                    *lowest = new_cost;
+                    queue.push(QueuedBoard { cost: new_cost, board: new_state });
+                }
+            }
+        }
+
+        progress_bar.finish_and_clear();
+        Ok(None)
+    }
+
+
+    /// Visits every board reachable from the board, ignoring the goal and 
+    /// `max_depth`, like the state-space clusters of puzzle databases.
+    /// 
+    /// Returns the hash of every reachable board, see `Solver.set_canonical`,
+    /// or `None` if there are more than `max_states` of them.
+    pub fn reachable_states(&self, max_states: usize) -> Option<HashSet<u64>> {
+        let mut archive = HashSet::new();
+        archive.insert(self.state_hash(&self.board));
+        let mut unvisited = vec![self.board.clone()];
+
+        while let Some(board) = unvisited.pop() {
+            // one-way vehicles can leave a board without any moves
+            for vehicle_move in board.possible_moves().unwrap_or_default() {
+                let mut new_state = board.clone();
+                new_state.move_vehicle(vehicle_move);
+                if archive.insert(self.state_hash(&new_state)) {
+                    if archive.len() > max_states {
+                        return None;
+                    }
+                    unvisited.push(new_state);
+                }
+            }
+        }
+        Some(archive)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Either the truck B drives down 3 tiles for the X car to pass, or the
+    /// car C moves aside so B only has to drive up 1 tile.
+    const DETOUR: &str = "\
+. . . .
+. C C .
+. . B .
+. . B .
+X X B .
+. . . .
+. . . .
+. . . .
+";
+
+
+    /// Returns the cheapest solution of `DETOUR` under `cost_model`.
+    fn cheapest(cost_model: CostModel) -> Vec<Move> {
+        let mut solver = Solver::from_board(DETOUR.parse().unwrap(), usize::MAX);
+        solver.set_show_progress(false);
+        solver.set_cost_model(cost_model);
+        solver.uniform_cost().unwrap().unwrap().history()
+    }
+
+
+    #[test]
+    fn slides_and_steps_pick_different_solutions() {
+        let mut solver = Solver::from_board(DETOUR.parse().unwrap(), usize::MAX);
+        solver.set_show_progress(false);
+        let shortest = solver.breadth_first().unwrap().unwrap().history();
+        assert_eq!(shortest.len(), 2);
+        assert_eq!(CostModel::Steps.total(&shortest), 5);
+        assert_eq!(cheapest(CostModel::Slides).len(), 2);
+
+        let fewest_steps = cheapest(CostModel::Steps);
+        assert_eq!(fewest_steps.len(), 3);
+        assert_eq!(CostModel::Steps.total(&fewest_steps), 4);
+        assert_eq!(fewest_steps[0], Move::new("C", -1).unwrap());
+    }
+
+
+    #[test]
+    fn weighted_vehicles_cost_their_weight_per_tile() {
+        let weights = |weights: &[(&str, u64)]| CostModel::Weighted(weights.iter()
+            .map(|(name, weight)| (VehicleId::new(name).unwrap(), *weight))
+            .collect()
+        );
+
+        // moving the heavy truck as little as possible pays off
+        let model = weights(&[("B", 5)]);
+        let solution = cheapest(model.clone());
+        assert_eq!(solution.len(), 3);
+        assert_eq!(model.total(&solution), 8);
+
+        // unless the car in its way is heavier still
+        let model = weights(&[("C", 10)]);
+        let solution = cheapest(model.clone());
+        assert_eq!(solution.len(), 2);
+        assert_eq!(model.total(&solution), 5);
+    }
+
+
+    #[test]
+    fn the_cost_limits_the_search() {
+        let mut solver = Solver::from_board(DETOUR.parse().unwrap(), 3);
+        solver.set_show_progress(false);
+        solver.set_cost_model(CostModel::Steps);
+        assert!(solver.uniform_cost().unwrap().is_none());
+        solver.set_max_depth(4);
+        assert!(solver.uniform_cost().unwrap().is_some());
+    }
+
+
+    /// `DETOUR` after moving C to the left and B up, which takes 2 moves.
+    const DETOUR_MOVED: &str = "\
+. . . .
+C C B .
+. . B .
+. . B .
+X X . .
+. . . .
+. . . .
+. . . .
+";
+
+
+    /// Returns a `Solver` for `DETOUR` searching for `goal`.
+    fn solver_with_goal(goal: Goal) -> Solver {
+        let mut solver = Solver::from_board(DETOUR.parse().unwrap(), usize::MAX);
+        solver.set_show_progress(false);
+        solver.set_goal(goal);
+        solver
+    }
+
+
+    #[test]
+    fn goal_boards_are_reached_by_the_fewest_moves() {
+        let goal: Board = DETOUR_MOVED.parse().unwrap();
+        let solver = solver_with_goal(Goal::Board(Box::new(goal.clone())));
+        let solved = solver.breadth_first().unwrap().unwrap();
+        assert_eq!(solved.history().len(), 2);
+        assert_eq!(solved.to_string(), goal.to_string());
+        assert_eq!(solver.uniform_cost().unwrap().unwrap().history().len(), 2);
+    }
+
+
+    #[test]
+    fn partial_goals_leave_the_other_vehicles_anywhere() {
+        let goal: Board = DETOUR_MOVED.parse().unwrap();
+        let truck = goal.vehicles().into_iter()
+            .filter(|vehicle| vehicle.id.name() == "B")
+            .collect();
+        let solved = solver_with_goal(Goal::Vehicles(truck)).breadth_first().unwrap().unwrap();
+        assert_eq!(solved.history(), vec![Move::new("C", -1).unwrap(), Move::new("B", -1).unwrap()]);
+
+        // the goal is reached without moving the X car, unlike `Goal::Exit`
+        let start: Board = DETOUR.parse().unwrap();
+        let car = start.vehicles().into_iter()
+            .filter(|vehicle| vehicle.id == VehicleId::X)
+            .collect();
+        let solved = solver_with_goal(Goal::Vehicles(car)).breadth_first().unwrap().unwrap();
+        assert!(solved.history().is_empty());
+    }
+
+
+    #[test]
+    fn goals_with_other_pieces_are_rejected() {
+        let other_pieces = [
+            // C is named D
+            DETOUR_MOVED.replace('C', "D"),
+            // C is a truck
+            DETOUR_MOVED.replacen("C C B .", "C C C B", 1).replacen(". . B .\n. . B .\nX", ". . . B\n. . . B\nX", 1),
+            // the board is a row smaller
+            DETOUR_MOVED.replacen(". . . .\n", "", 1),
+        ];
+        for goal in other_pieces {
+            let solver = solver_with_goal(Goal::Board(Box::new(goal.parse().unwrap())));
+            assert_eq!(solver.breadth_first().unwrap_err(), Error::UnreachableGoal, "{goal}");
+            assert_eq!(solver.uniform_cost().unwrap_err(), Error::UnreachableGoal, "{goal}");
+        }
+
+        let missing = VehiclePlacement::new("D", Direction::Horizontal, 0, 0, 2).unwrap();
+        let solver = solver_with_goal(Goal::Vehicles(vec![missing]));
+        assert_eq!(solver.breadth_first().unwrap_err(), Error::UnreachableGoal);
+        let longer = VehiclePlacement::new("C", Direction::Horizontal, 0, 0, 3).unwrap();
+        let solver = solver_with_goal(Goal::Vehicles(vec![longer]));
+        assert_eq!(solver.breadth_first().unwrap_err(), Error::UnreachableGoal);
     }
-}
\ No newline at end of file
+}
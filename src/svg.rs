@@ -0,0 +1,254 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::board::{Board, Direction, Move, Side, VehicleId, VehiclePlacement};
+use crate::error::Error;
+
+
+/// Width and height of a single tile in pixels.
+const TILE_SIZE: usize = 60;
+/// Empty space between a vehicle and the edges of its tiles.
+const VEHICLE_MARGIN: usize = 5;
+/// Empty space around a board, leaving room for the exit marker.
+const BOARD_MARGIN: usize = 20;
+/// Height of the caption above every frame of a solution strip.
+const CAPTION_HEIGHT: usize = 30;
+/// Fill color of walls.
+const WALL_COLOR: &str = "#555555";
+
+/// Fill color of the target vehicle, usually the red "X" car.
+const X_COLOR: &str = "#d62728";
+/// Light fill colors used for cars, with black labels.
+const CAR_COLORS: [&str; 12] = [
+    "#1f9bff", "#2ca02c", "#ffd700", "#ff8c00", "#a05fd2", "#17becf",
+    "#ff77c8", "#aee03c", "#8fb8ff", "#d2b48c", "#5fe0b0", "#b39ddb",
+];
+/// Dark fill colors used for trucks, with white labels.
+const TRUCK_COLORS: [&str; 6] = [
+    "#1a237e", "#1b5e20", "#6d4c1d", "#4a148c", "#004d40", "#5d5d1a",
+];
+
+
+/// Returns the fill and label colors of a vehicle, matching the colors
+/// used by `Board.show` in the terminal. The `target` vehicle is red. Free
+/// pieces get the colors of cars, whatever their size.
+pub(crate) fn vehicle_colors(vehicle: &VehiclePlacement, target: VehicleId) -> (&'static str, &'static str) {
+    if vehicle.id == target {
+        (X_COLOR, "white")
+    } else if vehicle.length >= 3 && vehicle.direction != Direction::Free {
+        (TRUCK_COLORS[vehicle.id.color_number() % TRUCK_COLORS.len()], "white")
+    } else {
+        (CAR_COLORS[vehicle.id.color_number() % CAR_COLORS.len()], "black")
+    }
+}
+
+
+/// Escapes the characters of `text` with a meaning in XML and HTML, so it 
+/// can be put in the text of an element or an attribute value.
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+
+/// Width and height in pixels of a drawn `Board`, including its margins.
+fn board_dimensions(board: &Board) -> (usize, usize) {
+    (
+        board.width() * TILE_SIZE + 2 * BOARD_MARGIN,
+        board.height() * TILE_SIZE + 2 * BOARD_MARGIN,
+    )
+}
+
+
+/// Draws the `board` as an SVG group, with the top left corner of the grid
+/// at `(BOARD_MARGIN, BOARD_MARGIN)`. The `highlight` vehicle gets a thick
+/// outline. Free pieces are drawn one row at a time, labeled on their top row.
+fn board_group(board: &Board, highlight: Option<VehicleId>) -> String {
+    let grid_width = board.width() * TILE_SIZE;
+    let grid_height = board.height() * TILE_SIZE;
+    let mut group = String::from("<g>\n");
+
+    // background and grid lines
+    let _ = writeln!(
+        group,
+        r##"<rect x="{BOARD_MARGIN}" y="{BOARD_MARGIN}" width="{grid_width}" height="{grid_height}" fill="#eeeeee" stroke="#333333" stroke-width="3"/>"##
+    );
+    for col in 1..board.width() {
+        let x = BOARD_MARGIN + col * TILE_SIZE;
+        let _ = writeln!(
+            group,
+            r##"<line x1="{x}" y1="{BOARD_MARGIN}" x2="{x}" y2="{}" stroke="#cccccc"/>"##,
+            BOARD_MARGIN + grid_height,
+        );
+    }
+    for row in 1..board.height() {
+        let y = BOARD_MARGIN + row * TILE_SIZE;
+        let _ = writeln!(
+            group,
+            r##"<line x1="{BOARD_MARGIN}" y1="{y}" x2="{}" y2="{y}" stroke="#cccccc"/>"##,
+            BOARD_MARGIN + grid_width,
+        );
+    }
+
+    for (row, col) in board.walls() {
+        let _ = writeln!(
+            group,
+            r#"<rect x="{}" y="{}" width="{TILE_SIZE}" height="{TILE_SIZE}" fill="{WALL_COLOR}"/>"#,
+            BOARD_MARGIN + col * TILE_SIZE,
+            BOARD_MARGIN + row * TILE_SIZE,
+        );
+    }
+
+    let mut labeled = HashSet::new();
+    for vehicle in board.vehicles() {
+        let (width, height) = match vehicle.direction {
+            Direction::Horizontal | Direction::Free => (usize::from(vehicle.length), 1),
+            Direction::Vertical => (1, usize::from(vehicle.length)),
+        };
+        let x = BOARD_MARGIN + vehicle.col * TILE_SIZE + VEHICLE_MARGIN;
+        let y = BOARD_MARGIN + vehicle.row * TILE_SIZE + VEHICLE_MARGIN;
+        let width = width * TILE_SIZE - 2 * VEHICLE_MARGIN;
+        let height = height * TILE_SIZE - 2 * VEHICLE_MARGIN;
+        let (fill, label_color) = vehicle_colors(&vehicle, board.target());
+        let outline = if highlight == Some(vehicle.id) {
+            r##"stroke="#000000" stroke-width="5""##
+        } else {
+            r##"stroke="#333333" stroke-width="1""##
+        };
+
+        let _ = writeln!(
+            group,
+            r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" rx="8" fill="{fill}" {outline}/>"#
+        );
+        if !labeled.insert(vehicle.id) {
+            continue;
+        }
+        let _ = writeln!(
+            group,
+            r#"<text x="{}" y="{}" font-family="sans-serif" font-size="24" font-weight="bold" text-anchor="middle" dominant-baseline="central" fill="{label_color}">{}</text>"#,
+            x + width / 2,
+            y + height / 2,
+            escape_xml(&vehicle.id_string()),
+        );
+
+    }
+
+    if let Ok(exit) = board.exit() {
+        group.push_str(&exit_marker(board, exit.side, exit.position));
+    }
+
+    group.push_str("</g>\n");
+    group
+}
+
+
+/// Draws the opening in the border of the `board` at the exit, with an arrow
+/// pointing out of the board.
+fn exit_marker(board: &Board, side: Side, position: usize) -> String {
+    let grid_width = board.width() * TILE_SIZE;
+    let grid_height = board.height() * TILE_SIZE;
+    let tile_start = BOARD_MARGIN + position * TILE_SIZE;
+
+    // the middle of the opening and the direction pointing out of the board
+    let (x, y, dx, dy): (isize, isize, isize, isize) = match side {
+        Side::Right => ((BOARD_MARGIN + grid_width) as isize, (tile_start + TILE_SIZE / 2) as isize, 1, 0),
+        Side::Left => (BOARD_MARGIN as isize, (tile_start + TILE_SIZE / 2) as isize, -1, 0),
+        Side::Bottom => ((tile_start + TILE_SIZE / 2) as isize, (BOARD_MARGIN + grid_height) as isize, 0, 1),
+        Side::Top => ((tile_start + TILE_SIZE / 2) as isize, BOARD_MARGIN as isize, 0, -1),
+    };
+    let half_opening = (TILE_SIZE / 2 - 2) as isize;
+    let (base, tip) = (4, BOARD_MARGIN as isize - 4);
+
+    format!(
+        r##"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="#eeeeee" stroke-width="5"/>
+<polygon points="{},{} {},{} {},{}" fill="{X_COLOR}"/>
+"##,
+        x - dy * half_opening, y - dx * half_opening,
+        x + dy * half_opening, y + dx * half_opening,
+        x + dx * base - dy * 8, y + dy * base - dx * 8,
+        x + dx * base + dy * 8, y + dy * base + dx * 8,
+        x + dx * tip, y + dy * tip,
+    )
+}
+
+
+/// Wraps SVG elements in an `<svg>` document of the given size.
+fn svg_document(width: usize, height: usize, elements: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\n{elements}</svg>\n"
+    )
+}
+
+
+/// Renders the `board` as an SVG image, with a grid, labeled vehicles and an
+/// exit marker, see `Board.exit`.
+pub fn board_svg(board: &Board) -> String {
+    let (width, height) = board_dimensions(board);
+    svg_document(width, height, &board_group(board, None))
+}
+
+
+/// Renders every state of a solution as a separate SVG image. The first
+/// image is the starting `board`, every next image has the moved vehicle
+/// outlined.
+///
+/// Returns an error if one of the `moves` is not possible.
+pub fn solution_frames(board: &Board, moves: &[Move]) -> Result<Vec<String>, Error> {
+    let (width, height) = board_dimensions(board);
+    let mut board = board.clone();
+    let mut frames = vec![svg_document(width, height, &board_group(&board, None))];
+
+    for veh_move in moves {
+        board.try_move_vehicle(veh_move.clone())?;
+        frames.push(svg_document(width, height, &board_group(&board, Some(veh_move.vehicle_id))));
+    }
+    Ok(frames)
+}
+
+
+/// Renders every state of a solution next to each other in a single SVG
+/// image, wrapping after `columns` frames. Every frame is captioned with the
+/// move that led to it.
+///
+/// Returns an error if one of the `moves` is not possible.
+pub fn solution_strip(board: &Board, moves: &[Move], columns: usize) -> Result<String, Error> {
+    let (frame_width, board_height) = board_dimensions(board);
+    let frame_height = board_height + CAPTION_HEIGHT;
+    let columns = columns.clamp(1, moves.len() + 1);
+    let rows = (moves.len() + 1).div_ceil(columns);
+
+    let mut board = board.clone();
+    let mut elements = String::new();
+
+    for frame in 0..=moves.len() {
+        let (caption, highlight) = if frame == 0 {
+            (String::from("Start"), None)
+        } else {
+            let veh_move = &moves[frame - 1];
+            board.try_move_vehicle(veh_move.clone())?;
+            (
+                escape_xml(&format!("Move {frame}: {veh_move}")),
+                Some(veh_move.vehicle_id),
+            )
+        };
+
+        let x = (frame % columns) * frame_width;
+        let y = (frame / columns) * frame_height;
+        let _ = writeln!(
+            elements,
+            r#"<g transform="translate({x},{y})"><text x="{}" y="{}" font-family="sans-serif" font-size="18" text-anchor="middle">{caption}</text>"#,
+            frame_width / 2,
+            CAPTION_HEIGHT - 5,
+        );
+        let _ = writeln!(
+            elements,
+            r#"<g transform="translate(0,{CAPTION_HEIGHT})">{}</g></g>"#,
+            board_group(&board, highlight),
+        );
+    }
+
+    Ok(svg_document(columns * frame_width, rows * frame_height, &elements))
+}
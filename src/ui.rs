@@ -1,84 +1,715 @@
 
-// mod board;
-#[path ="solvers.rs"]
-pub mod solvers;
-
+use std::collections::HashMap;
 use std::fs::{self, ReadDir};
+use std::io::{self, IsTerminal, Read};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
 
-use solvers::board::Board;
+use rusthour::board::{Board, Move, VehicleId};
+use rusthour::collection::Collection;
+use rusthour::convert::{self, BoardFormat};
+use rusthour::error::Error;
+use rusthour::notation;
+use rusthour::solvers::{CostModel, Solver};
+use clap::ValueEnum;
 use read_input::prelude::*;
 use regex::Regex;
+use serde::Serialize;
+
+
+/// Output format of the commands that print results.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Human readable text.
+    #[default]
+    Text,
+    /// A single JSON document.
+    Json,
+}
+
+
+/// The cost models selectable on the command line, see `CostModel`.
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Cost {
+    /// Every move costs 1, however far the vehicle slides.
+    #[default]
+    Slides,
+    /// Every tile a vehicle slides over costs 1.
+    Steps,
+    /// Every tile a vehicle slides over costs its weight, by default the
+    /// length of the vehicle.
+    Weighted,
+}
+
+
+impl Cost {
+    /// Returns the `CostModel` for the vehicles on `board`. Weighted 
+    /// vehicles not listed in `weights` weigh as much as their length.
+    pub fn model(self, board: &Board, weights: &[(String, u64)]) -> Result<CostModel, Error> {
+        match self {
+            Cost::Slides => Ok(CostModel::Slides),
+            Cost::Steps => Ok(CostModel::Steps),
+            Cost::Weighted => {
+                let mut model = board.vehicles().into_iter()
+                    .map(|vehicle| (vehicle.id, u64::from(vehicle.length)))
+                    .collect::<HashMap<_, _>>();
+                for (name, weight) in weights {
+                    model.insert(VehicleId::new(name)?, *weight);
+                }
+                Ok(CostModel::Weighted(model))
+            }
+        }
+    }
+}
+
+
+/// Parses the weight of a vehicle given on the command line as "NAME=WEIGHT".
+pub fn parse_weight(text: &str) -> Result<(String, u64), String> {
+    text.split_once('=')
+        .and_then(|(name, weight)| Some((name.to_string(), weight.parse().ok()?)))
+        .ok_or(format!("{text} is not a weight like A=3"))
+}
+
+
+/// Extension of the puzzle collection files in the gameboards directory, 
+/// shared with gameboards in the JSON format, see `is_collection`.
+const COLLECTION_EXTENSION: &str = "json";
+/// Extensions of the gameboard files in the gameboards directory: the csv 
+/// format and text grids.
+const BOARD_EXTENSIONS: [&str; 2] = ["csv", "txt"];
+/// Directory searched for gameboards when no other is set.
+const DEFAULT_BOARDS_DIR: &str = "./gameboards";
+/// File name that reads from stdin instead.
+pub const STDIN: &str = "-";
+
+/// Directory searched for gameboards, see `set_boards_dir`.
+static BOARDS_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Width and height of csv gameboards without a size in their name, see 
+/// `set_board_size`.
+static BOARD_SIZE: OnceLock<(u8, u8)> = OnceLock::new();
+
+
+/// Search for gameboards in `dir` instead of ./gameboards. Only the first
+/// call has an effect.
+pub fn set_boards_dir(dir: PathBuf) {
+    let _ = BOARDS_DIR.set(dir);
+}
+
+
+/// Read csv gameboards without the size in their file name, like those on
+/// stdin, as boards of `width` columns and `height` rows. Only the first call
+/// has an effect.
+pub fn set_board_size(width: u8, height: u8) {
+    let _ = BOARD_SIZE.set((width, height));
+}
+
+
+/// Returns the directory searched for gameboards.
+pub fn boards_dir() -> &'static Path {
+    BOARDS_DIR.get().map_or(Path::new(DEFAULT_BOARDS_DIR), |dir| dir.as_path())
+}
 
 
 pub fn list_boards() -> Result<ReadDir, std::io::Error> {
-    fs::read_dir("./gameboards")
+    fs::read_dir(boards_dir())
+}
+
+
+/// Read the file `filename`, or stdin if it is `STDIN`.
+pub fn read_source(filename: &str) -> io::Result<String> {
+    if filename != STDIN {
+        return fs::read_to_string(filename);
+    }
+    let mut contents = String::new();
+    io::stdin().read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+
+/// Returns `true` if the JSON file at `path` holds a puzzle collection 
+/// rather than a single gameboard, as only collections have puzzles. Files
+/// that can't be read as JSON count as collections, so they are reported as
+/// invalid collections.
+fn is_collection(path: &Path) -> bool {
+    fs::read_to_string(path).ok()
+        .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+        .is_none_or(|json| json.get("puzzles").is_some())
 }
 
 
-pub fn print_boards() {
-    let paths = list_boards().unwrap();
+/// Returns the sorted paths of the files in the gameboards directory, either
+/// the puzzle collections or the gameboard files, which include JSON files
+/// holding a single gameboard. Files with other extensions are left out.
+pub fn gameboard_paths(collections: bool) -> Vec<PathBuf> {
+    let Ok(entries) = list_boards() else {
+        return vec![];
+    };
+    let mut paths = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| if extension == COLLECTION_EXTENSION {
+                is_collection(path) == collections
+            } else {
+                !collections && BOARD_EXTENSIONS.contains(&extension)
+            })
+        )
+        .collect::<Vec<_>>();
+    // read_dir gives no guarantees about the order
+    paths.sort();
+    paths
+}
+
+
+/// Returns every puzzle collection in the gameboards directory, with its path.
+/// Files that are not valid collections are skipped with a warning.
+pub fn list_collections() -> Vec<(String, Collection)> {
+    gameboard_paths(true).into_iter()
+        .filter_map(|path| {
+            let path = path.display().to_string();
+            match Collection::load(&path) {
+                Ok(collection) => Some((path, collection)),
+                Err(error) => {
+                    eprintln!("Skipped {path}, it is not a valid puzzle collection: {error}.");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+
+/// Returns the name of a gameboard, eg "6x6_1" for "Rushhour6x6_1.csv", or 
+/// `None` if the file name contains no valid name.
+pub fn board_name(filename: &str) -> Option<String> {
     // number, letter x, number, underscore, an identifier
     let re = Regex::new(r"(?<name>\d+x\d+_[0-9a-zA-Z]+)").unwrap();
 
-    for path in paths {
+    // variable to put the name in
+    let mut name = String::new();
+    // run regex and do magic
+    let file_name = Path::new(filename).file_name()?.to_str()?;
+    re.captures(file_name)?
+        // put result in name variable
+        .expand("$name", &mut name);
+
+    Some(name)
+}
+
+
+/// Returns `board_name` as part of the name of a file in the results 
+/// directory. Names of puzzles in collections are free text, so every 
+/// character but letters, digits, "-", "_" and "." is replaced by "_", eg
+/// "Puzzle 1/3" gives "Puzzle_1_3".
+pub fn result_file_name(board_name: &str) -> String {
+    board_name.chars()
+        .map(|char| if char.is_ascii_alphanumeric() || "-_.".contains(char) { char } else { '_' })
+        .collect()
+}
+
+
+/// A gameboard found by `print_boards`.
+#[derive(Debug, Clone, Serialize)]
+struct BoardEntry {
+    /// The gameboard file or the collection containing the board.
+    path: String,
+    name: Option<String>,
+    /// The one-line notation, if the board fits it.
+    notation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    difficulty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optimal_length: Option<usize>,
+    board: Board,
+}
+
+
+pub fn print_boards(format: Format) {
+    let mut entries = vec![];
+
+    for (path, board) in load_boards() {
+        entries.push(BoardEntry {
+            name: board_name(&path),
+            // only boards with single letter vehicles fit the one-line notation
+            notation: notation::serialize(&board).ok(),
+            path,
+            author: None,
+            difficulty: None,
+            optimal_length: None,
+            board,
+        });
+    }
+
+    for (path, collection) in list_collections() {
+        for puzzle in collection.puzzles {
+            entries.push(BoardEntry {
+                path: path.clone(),
+                name: Some(puzzle.name),
+                notation: notation::serialize(&puzzle.board).ok(),
+                author: puzzle.author,
+                difficulty: puzzle.difficulty,
+                optimal_length: puzzle.optimal_length,
+                board: puzzle.board,
+            });
+        }
+    }
+
+    if format == Format::Json {
+        println!("{}", serde_json::to_string_pretty(&entries).expect("Serializing boards failed."));
+        return;
+    }
+
+    for entry in entries {
         // print the path
-        println!("Path: {}", path.as_ref().unwrap().path().display());
-
-        // variable to put the name in
-        let mut name = String::new();
-        // run regex and do magic
-        re.captures(
-            path.map(|dir_entry| dir_entry.file_name())
-            .unwrap()
-            .to_str()
-            .unwrap()
+        println!("Path: {}", entry.path);
+        // set to placeholder when there is no valid name
+        println!("Name: {}", entry.name.unwrap_or(String::from("No valid name.")));
+        if let Some(notation) = entry.notation {
+            println!("Notation: {}", notation);
+        }
+        if let Some(author) = entry.author {
+            println!("Author: {}", author);
+        }
+        if let Some(difficulty) = entry.difficulty {
+            println!("Difficulty: {}", difficulty);
+        }
+        if let Some(optimal_length) = entry.optimal_length {
+            println!("Optimal length: {}", optimal_length);
+        }
+    }
+}
+
+
+/// Read the gameboard in the file `filename`, see `parse_board`.
+/// 
+/// Returns an error if the file can't be read or is not a valid gameboard.
+pub fn load_board(filename: &str) -> Result<Board, Error> {
+    let contents = fs::read_to_string(filename)
+        .map_err(|error| Error::Io(format!("{filename}: {error}")))?;
+    parse_board(filename, &contents)
+}
+
+
+/// Returns every gameboard file in the gameboards directory, with its path.
+/// Files that are not valid gameboards are skipped with a warning.
+pub fn load_boards() -> Vec<(String, Board)> {
+    gameboard_paths(false).into_iter()
+        .filter_map(|path| {
+            let path = path.display().to_string();
+            match load_board(&path) {
+                Ok(board) => Some((path, board)),
+                Err(error) => {
+                    eprintln!("Skipped {path}, it is not a valid gameboard: {error}.");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+
+/// Parse the `contents` of a gameboard file in any format read by `convert`, 
+/// detected from the contents. The size of a csv board is taken from its 
+/// `name`, eg "Rushhour6x6_1.csv", or else from `set_board_size`.
+pub fn parse_board(name: &str, contents: &str) -> Result<Board, Error> {
+    let size = convert::dimensions_from_name(name).or(BOARD_SIZE.get().copied());
+    convert::read(BoardFormat::detect(contents), name, contents, size)
+}
+
+
+/// Returns the path of the first gameboard whose file name contains 
+/// `board_name`, eg "6x6_1" finds "./gameboards/Rushhour6x6_1.csv".
+pub fn find_board(board_name: &str) -> Option<String> {
+    gameboard_paths(false).into_iter()
+        .find(|path| path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.contains(board_name))
         )
-            // put result in name variable
-            .map(|result| result.expand("$name", &mut name));
+        .map(|path| path.display().to_string())
+}
+
 
-        // set to placeholder when result was empty
-        if name == "" {
-            name = String::from("No valid name.");
+/// Returns the name and the starting position of the gameboard `name`, which
+/// is read from stdin for `STDIN` or from the file if `name` is a path. 
+/// Otherwise searches the gameboard files first, see `find_board`, and then 
+/// the puzzles of all collections, see `Collection.find`.
+/// 
+/// Boards in the csv format on stdin, or in files without the size in their
+/// name, are only read when the size is set, see `set_board_size`.
+pub fn open_board(name: &str) -> Option<(String, Board)> {
+    let path = Path::new(name);
+    if name == STDIN || path.is_file() {
+        let contents = read_source(name).ok()?;
+        let mut board = parse_board(name, &contents).ok()?;
+        board.clear_history();
+        let name = match board_name(name) {
+            _ if name == STDIN => String::from("stdin"),
+            Some(name) => name,
+            None => path.file_stem()?.to_string_lossy().to_string(),
+        };
+        return Some((name, board));
+    }
+
+    if let Some(filename) = find_board(name) {
+        let mut board = load_board(&filename).ok()?;
+        board.clear_history();
+        return Some((board_name(&filename).unwrap_or(filename), board));
+    }
+
+    list_collections().into_iter()
+        .find_map(|(_, collection)| {
+            let puzzle = collection.find(name)?;
+            let mut board = puzzle.board.clone();
+            board.clear_history();
+            Some((puzzle.name.clone(), board))
+        })
+}
+
+
+/// Result of a manually played game.
+#[derive(Debug, Clone, Copy)]
+pub struct Score {
+    /// Number of moves made, sliding a vehicle any distance counts as one.
+    pub moves: u64,
+    /// Number of tiles moved by all vehicles together.
+    pub steps: u64,
+    /// Number of moves of the shortest solution.
+    pub par: usize,
+}
+
+
+impl Score {
+    /// Number of moves made above par.
+    pub fn over_par(&self) -> i64 {
+        self.moves as i64 - self.par as i64
+    }
+}
+
+
+/// The par and the best scores of a gameboard. Stored in the results 
+/// directory, so the shortest solution only has to be calculated once.
+/// 
+/// The record belongs to the starting board with the canonical form 
+/// `board`, see `Board.canonical`, stored as a text grid so another board 
+/// with the same name does not inherit its par and scores.
+#[derive(Debug, Clone, Default)]
+struct ScoreRecord {
+    board: String,
+    par: Option<usize>,
+    best_moves: Option<u64>,
+    best_steps: Option<u64>,
+}
+
+
+impl ScoreRecord {
+    /// Location of the record of the gameboard with the given name, see 
+    /// `result_file_name`.
+    fn path(board_name: &str) -> String {
+        format!("results/{}_score.csv", result_file_name(board_name))
+    }
+
+
+    /// Read the record of the gameboard with the given name, starting from 
+    /// `start`. Returns an empty record if the board has not been scored 
+    /// before, or the record belongs to another board with the same name.
+    fn load(board_name: &str, start: &Board) -> ScoreRecord {
+        let board = start.canonical().to_string();
+        let empty = ScoreRecord { board: board.clone(), ..ScoreRecord::default() };
+        let Ok(mut reader) = csv::Reader::from_path(ScoreRecord::path(board_name)) else {
+            return empty;
+        };
+
+        reader.deserialize::<(String, Option<usize>, Option<u64>, Option<u64>)>()
+            .next()
+            .and_then(|record| record.ok())
+            .filter(|(grid, ..)| *grid == board)
+            .map(|(board, par, best_moves, best_steps)| ScoreRecord { board, par, best_moves, best_steps })
+            .unwrap_or(empty)
+    }
+
+
+    /// Write the record of the gameboard with the given name.
+    fn save(&self, board_name: &str) {
+        let mut writer = csv::Writer::from_path(ScoreRecord::path(board_name))
+            .expect("Making writer failed.");
+        writer.write_record(["board", "par", "best_moves", "best_steps"]).expect("Writing heading failed.");
+        writer.serialize((&self.board, self.par, self.best_moves, self.best_steps)).expect("Writing entry failed.");
+        writer.flush().expect("Flushing failed.");
+    }
+
+
+    /// Returns the par of the gameboard, solving `start` if the par is not
+    /// known yet, or `None` if `start` can't be solved.
+    fn par(&mut self, board_name: &str, start: &Board) -> Result<Option<usize>, Error> {
+        if self.par.is_some() {
+            return Ok(self.par);
+        }
+
+        println!("Calculating par...");
+        let Some(solution) = optimal_moves(start)? else {
+            return Ok(None);
+        };
+        self.par = Some(solution.len());
+        self.save(board_name);
+        Ok(self.par)
+    }
+
+
+    /// Remember the `score` if it beats the best score so far. Returns 
+    /// `true` if either the moves or the steps are a new best.
+    fn update(&mut self, board_name: &str, score: &Score) -> bool {
+        let new_best_moves = self.best_moves.is_none_or(|best| score.moves < best);
+        let new_best_steps = self.best_steps.is_none_or(|best| score.steps < best);
+
+        if new_best_moves {
+            self.best_moves = Some(score.moves);
         }
-        println!("Name: {}", name);
+        if new_best_steps {
+            self.best_steps = Some(score.steps);
+        }
+        self.save(board_name);
+        new_best_moves || new_best_steps
+    }
+}
+
+
+/// Input that asks for a hint instead of moving a vehicle.
+const HINT_COMMANDS: [&str; 2] = ["?", "HINT"];
+/// Input that saves the current position as a new gameboard.
+const SAVE_COMMAND: &str = "SAVE";
+/// Input that takes back the last move.
+const UNDO_COMMAND: &str = "UNDO";
+/// Input that stops the game, to be continued later with `resume`.
+const QUIT_COMMAND: &str = "QUIT";
+
+
+/// Returns `true` if `typed` is one of the commands of manual games, in 
+/// any case.
+fn is_command(typed: &str) -> bool {
+    let typed = typed.to_uppercase();
+    HINT_COMMANDS.contains(&typed.as_str()) || [SAVE_COMMAND, UNDO_COMMAND, QUIT_COMMAND].contains(&typed.as_str())
+}
+
+
+/// Returns the vehicle among `vehicles` the player means by `typed`: the 
+/// vehicle named exactly `typed`, or else the only vehicle named `typed` 
+/// in another case.
+fn typed_vehicle(typed: &str, vehicles: &[String]) -> Option<String> {
+    if vehicles.iter().any(|vehicle| vehicle == typed) {
+        return Some(typed.to_string());
+    }
+    let typed = typed.to_lowercase();
+    let mut matches = vehicles.iter().filter(|vehicle| vehicle.to_lowercase() == typed);
+    match (matches.next(), matches.next()) {
+        (Some(vehicle), None) => Some(vehicle.clone()),
+        _ => None,
     }
 }
 
 
-pub fn play(filename: &str) -> Result<u64, ()> {
-    let re = Regex::new(r"\d+").unwrap();
-    let Some(size) = re.captures(filename) else {panic!("Regex failed.")};
-    let mut board = Board::new(size[0].parse::<u8>().expect("Parsing failed."));
-    board.fill(filename);
+/// Returns the moves of a shortest solution starting from `board`, or 
+/// `None` if the board can't be solved.
+fn optimal_moves(board: &Board) -> Result<Option<Vec<Move>>, Error> {
+    let mut start = board.clone();
+    start.clear_history();
+
+    let mut solver = Solver::from_board(start, usize::MAX);
+    solver.set_show_progress(false);
+    Ok(solver.breadth_first()?.map(|solved| solved.history()))
+}
+
+
+/// Prints the next move of a shortest solution from `board` and how many 
+/// moves remain at best. `par` is the length of the shortest solution from
+/// the starting board, if known, used to warn the player when they are off
+/// track.
+fn show_hint(board: &Board, score: u64, par: Option<usize>) -> Result<(), Error> {
+    let Some(solution) = optimal_moves(board)? else {
+        println!("This board can't be solved anymore.");
+        return Ok(());
+    };
+    let Some(next_move) = solution.first() else {
+        println!("The board is already solved.");
+        return Ok(());
+    };
+
+    println!(
+        "Hint: move {}. At best {} move(s) remain.", 
+        next_move,
+        solution.len()
+    );
+
+    let Some(par) = par else {
+        return Ok(());
+    };
+    // the best possible result from here compared to the best from the start
+    let detour = (score as usize + solution.len()).saturating_sub(par);
+    if detour > 0 {
+        println!(
+            "You wandered off the shortest path: you will need at least {detour} move(s) more than the optimal {par}."
+        );
+    }
+    Ok(())
+}
+
+
+/// Location the game in progress on the gameboard with the given name is 
+/// saved to after every move, see `result_file_name`.
+pub fn save_path(board_name: &str) -> String {
+    format!("results/{}.save", result_file_name(board_name))
+}
+
+
+/// Write a game in progress to `file_path`: the name of the gameboard, the
+/// contents of the gameboard file it started from and the moves made since,
+/// separated by empty lines.
+fn save_game(file_path: &str, board_name: &str, board_contents: &str, board: &Board) {
+    let contents = format!(
+        "board,{board_name}\n\n{}\n\n{}", 
+        board_contents.trim(), 
+        board.history_csv()
+    );
+    fs::write(file_path, contents).expect("Saving game failed.");
+}
+
+
+/// Play the gameboard `board` called `name`. The game is saved after every
+/// move and can be continued with `resume`.
+/// 
+/// Returns `None` if the player quits before solving the board.
+pub fn play(name: &str, board: &Board) -> Result<Option<Score>, Error> {
+    // the text grid does not need the size in the name, unlike the csv format
+    play_from(name, &board.to_string(), vec![])
+}
+
+
+/// Continue a game saved during `play`.
+pub fn resume(save_file: &str) -> Result<Option<Score>, Error> {
+    let contents = fs::read_to_string(save_file)
+        .map_err(|error| Error::Io(format!("{save_file}: {error}")))?;
+    let mut sections = contents.split("\n\n");
+    let invalid = || Error::Parse(format!("saved game {save_file}"));
+
+    let name = sections.next()
+        .and_then(|line| line.trim().strip_prefix("board,"))
+        .ok_or_else(invalid)?;
+    let board_contents = sections.next().ok_or_else(invalid)?;
+    let history = Move::from_csv(sections.next().unwrap_or_default())?;
+
+    play_from(name, board_contents, history)
+}
+
+
+/// Play the gameboard `board_name` described by `board_contents`, after replaying
+/// the moves in `history`.
+/// 
+/// When no vehicle can move anymore, which one-way vehicles may cause, the 
+/// player can still ask for a hint, undo moves or quit.
+fn play_from(name: &str, board_contents: &str, history: Vec<Move>) -> Result<Option<Score>, Error> {
+    let mut board = parse_board(name, board_contents)?;
+    // undoing replays the moves made since the start
+    board.clear_history();
+    let start = board.clone();
 
+    // the par is only calculated once a hint or the final score needs it
+    let mut record = ScoreRecord::load(name, &start);
     let mut score = 0;
+    let mut steps = 0;
+
+    for previous_move in history {
+        steps += u64::from(previous_move.direction.unsigned_abs());
+        score += 1;
+        board.try_move_vehicle(previous_move)?;
+    }
 
+    let save_file = save_path(name);
     board.show(); 
+    println!(
+        "Type \"?\" or \"hint\" for a hint, \"undo\" to take back a move, \"save\" to save this \
+        position as a new gameboard or \"quit\" to stop. Your progress is saved to {save_file}."
+    );
     while !board.is_won()? {
-        let mut moves = board.possible_moves()?;
-        let available_vehicles = moves.iter().map(
+        let mut moves = board.possible_moves().unwrap_or_default();
+        if moves.is_empty() {
+            println!("No vehicle can move anymore. Type \"undo\" to take back a move, \"?\" for a hint or \"quit\" to stop.");
+        }
+        let mut available_vehicles = moves.iter().map(
                 |m| m.get_id_string()
             ).collect::<Vec<_>>();
+        available_vehicles.sort();
+        available_vehicles.dedup();
 
-        let chosen_vehicle: String = input()
+        let vehicles = available_vehicles.clone();
+        let typed: String = input()
             .repeat_msg("Vehicle to move: ")
             .err("Input parsing failed.")
-            .add_err_test(move |x: &String| available_vehicles.contains(&x.to_uppercase()), 
+            .add_err_test(move |x: &String| {
+                    typed_vehicle(x, &vehicles).is_some() || is_command(x)
+                }, 
                 "This vehicle cannot move."
-            ).get().to_uppercase();
+            ).get();
+        let command = typed.to_uppercase();
+
+        let chosen_vehicle = match typed_vehicle(&typed, &available_vehicles) {
+            // a vehicle named exactly like a command goes first
+            Some(vehicle) if vehicle == typed => vehicle,
+            _ if HINT_COMMANDS.contains(&command.as_str()) => {
+                // a board that can't be solved from the start has no par
+                let par = record.par(name, &start)?;
+                show_hint(&board, score, par)?;
+                continue;
+            }
+            _ if command == SAVE_COMMAND => {
+                // the csv format takes the size of the board from the file name
+                let size = format!("{}x{}", board.width(), board.height());
+                let stem = result_file_name(name.strip_prefix(&format!("{size}_")).unwrap_or(name));
+                let file_path = format!("results/Rushhour{size}_{stem}move{score}.csv");
+                board.save(&file_path);
+                println!("Saved this position as a new gameboard to {file_path}.");
+                continue;
+            }
+            _ if command == UNDO_COMMAND => {
+                let mut history = board.history();
+                let Some(last_move) = history.pop() else {
+                    println!("There is no move to undo.");
+                    continue;
+                };
+                board = start.clone();
+                for previous_move in history {
+                    board.move_vehicle(previous_move);
+                }
+                score -= 1;
+                steps -= u64::from(last_move.direction.unsigned_abs());
+                save_game(&save_file, name, board_contents, &board);
+                board.show();
+                continue;
+            }
+            _ if command == QUIT_COMMAND => {
+                save_game(&save_file, name, board_contents, &board);
+                println!("Continue this game later with `manual --resume {save_file}`.");
+                return Ok(None);
+            }
+            Some(vehicle) => vehicle,
+            None => unreachable!("The input is checked to be a vehicle or a command."),
+        };
 
         moves = moves.into_iter()
             .filter(|m| m.get_id_string() == chosen_vehicle)
             .collect::<Vec<_>>();
 
-        if moves.len() == 1 {
-            board.move_vehicle(moves.pop().unwrap());
+        let chosen_move = if moves.len() == 1 {
+            moves.pop().unwrap()
         } else {
-            let valid_distances: Vec<i8> = moves.iter()
-                .map(|m| m.direction)
+            let valid_distances: Vec<String> = moves.iter()
+                .map(Move::distance_string)
                 .collect();
 
-            let distance_input: i8 = input()
+            let distance_input: String = input()
                 .repeat_msg(format!("Distance to move (possible: {valid_distances:?}): "))
                 .err("Input parsing failed.")
                 .inside_err(
@@ -86,16 +717,117 @@ pub fn play(filename: &str) -> Result<u64, ()> {
                     "This vehicle cannot move there."
                 ).get();
             
-            board.move_vehicle(moves.into_iter().find(
-                |m| m.direction == distance_input).unwrap()
-            )
-        }
+            moves.into_iter().find(
+                |m| m.distance_string() == distance_input).unwrap()
+        };
         
         score += 1;
+        steps += u64::from(chosen_move.direction.unsigned_abs());
+        board.move_vehicle(chosen_move);
+        save_game(&save_file, name, board_contents, &board);
         board.show(); 
     }
 
     println!("You solved the game!");
     board.export("results/solution.csv");
-    Ok(score)
-}
\ No newline at end of file
+    // the game is finished, so there is nothing left to resume
+    let _ = fs::remove_file(&save_file);
+
+    let score = Score {
+        moves: score,
+        steps,
+        par: record.par(name, &start)?.expect("A solved board has a solution."),
+    };
+    if record.update(name, &score) {
+        println!("New best score!");
+    }
+    Ok(Some(score))
+}
+
+/// Show the moves in `solution_file` one at a time on the gameboard `board`,
+/// highlighting the vehicle that moved. Waits `delay` between moves, or for
+/// the enter key if there is no `delay`.
+/// 
+/// Returns an error if the solution contains a move that is not possible.
+pub fn replay(mut board: Board, solution_file: &str, delay: Option<Duration>) -> Result<(), Error> {
+    let contents = fs::read_to_string(solution_file)
+        .map_err(|error| Error::Io(format!("{solution_file}: {error}")))?;
+    let moves = Move::from_csv(&contents)?;
+    // only redraw in place when the output is a terminal
+    let redraw = io::stdout().is_terminal();
+
+    board.show();
+    println!("Starting position, {} moves to go.", moves.len());
+
+    for (turn, veh_move) in moves.into_iter().enumerate() {
+        match delay {
+            Some(delay) => thread::sleep(delay),
+            None => {
+                println!("Press enter for the next move.");
+                let _ = io::stdin().read_line(&mut String::new());
+            }
+        }
+
+        let description = veh_move.to_string();
+        let vehicle_id = veh_move.vehicle_id;
+        if let Err(error) = board.try_move_vehicle(veh_move) {
+            println!("Move {}: {description} is not possible.", turn + 1);
+            return Err(error);
+        }
+
+        if redraw {
+            // clear the screen and move the cursor to the top left
+            print!("\x1b[2J\x1b[H");
+        }
+        board.show_highlighted(vehicle_id);
+        println!("Move {}: {description}", turn + 1);
+    }
+
+    if board.is_won()? {
+        println!("The board is solved!");
+    } else {
+        println!("The board is not solved yet.");
+    }
+    Ok(())
+}
+
+
+/// Result of checking a solution against a gameboard.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    /// Name of the gameboard.
+    pub board: String,
+    /// Number of moves in the solution.
+    pub moves: usize,
+    /// Number of the first move that is not possible, starting at 1.
+    pub invalid_move: Option<usize>,
+    /// Whether the moves solve the gameboard.
+    pub solved: bool,
+}
+
+
+/// Check that the moves in `solution_file` are possible on the gameboard 
+/// `board` called `name`, and that they solve it.
+/// 
+/// Returns an error if the solution can't be read.
+pub fn verify(name: &str, mut board: Board, solution_file: &str) -> Result<Verification, Error> {
+    let contents = fs::read_to_string(solution_file)
+        .map_err(|error| Error::Io(format!("{solution_file}: {error}")))?;
+    let moves = Move::from_csv(&contents)?;
+
+    let mut verification = Verification {
+        board: name.to_string(),
+        moves: moves.len(),
+        invalid_move: None,
+        solved: false,
+    };
+
+    for (turn, veh_move) in moves.into_iter().enumerate() {
+        if board.try_move_vehicle(veh_move).is_err() {
+            verification.invalid_move = Some(turn + 1);
+            return Ok(verification);
+        }
+    }
+    verification.solved = board.is_won()?;
+    Ok(verification)
+}